@@ -0,0 +1,71 @@
+use bn254_hash2curve::hash2g1::{expand_message_into, hash_to_g1_batch, HashToG1};
+use bn254_hash2curve::multibuf::expand_message_batch;
+use bn254_hash2curve::hash2g2::{hash_to_g2_batch, HashToG2};
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+const DST_G1: &[u8] = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+const DST_G2: &[u8] = b"QUUX-V01-CS02-with-BN254G2_XMD:SHA-256_SVDW_RO_";
+const SIZES: [usize; 5] = [1, 10, 100, 1_000, 10_000];
+
+fn messages(n: usize) -> Vec<Vec<u8>> {
+    (0..n as u64).map(|i| i.to_be_bytes().to_vec()).collect()
+}
+
+fn bench_g1(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_to_g1");
+    group.sample_size(10);
+    for n in SIZES {
+        let msgs = messages(n);
+        let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("batch", n), &msgs, |b, msgs| {
+            b.iter(|| hash_to_g1_batch(black_box(msgs), DST_G1))
+        });
+        group.bench_with_input(BenchmarkId::new("sequential", n), &msgs, |b, msgs| {
+            b.iter(|| msgs.iter().map(|m| HashToG1(black_box(m), DST_G1)).collect::<Vec<_>>())
+        });
+    }
+    group.finish();
+}
+
+fn bench_g2(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hash_to_g2");
+    group.sample_size(10);
+    for n in SIZES {
+        let msgs = messages(n);
+        let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_with_input(BenchmarkId::new("batch", n), &msgs, |b, msgs| {
+            b.iter(|| hash_to_g2_batch(black_box(msgs), DST_G2))
+        });
+        group.bench_with_input(BenchmarkId::new("sequential", n), &msgs, |b, msgs| {
+            b.iter(|| msgs.iter().map(|m| HashToG2(black_box(m), DST_G2)).collect::<Vec<_>>())
+        });
+    }
+    group.finish();
+}
+
+fn bench_expand(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expand_message_xmd");
+    group.sample_size(10);
+    for n in SIZES {
+        let msgs = messages(n);
+        let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+        let mut out = vec![0u8; 128 * n];
+        group.throughput(Throughput::Elements(n as u64));
+        group.bench_function(BenchmarkId::new("batch", n), |b| {
+            b.iter(|| expand_message_batch(black_box(&msgs), DST_G2, 128, &mut out))
+        });
+        group.bench_function(BenchmarkId::new("sequential", n), |b| {
+            b.iter(|| {
+                for (m, o) in msgs.iter().zip(out.chunks_exact_mut(128)) {
+                    expand_message_into(black_box(m), DST_G2, o)
+                }
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_g1, bench_g2, bench_expand);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+use ark_bn254::Fq;
+use ark_ff::PrimeField;
+use ark_std::rand::RngCore;
+use bn254_hash2curve::hash2g1::FromOkm;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use num_bigint::BigUint;
+
+// the BigUint reduction FromOkm used before, kept as a baseline
+fn from_okm_biguint(data: &[u8; 48]) -> Fq {
+    let p: BigUint = Fq::MODULUS.into();
+    Fq::from(BigUint::from_bytes_be(&data[..]) % p)
+}
+
+fn bench_from_okm(c: &mut Criterion) {
+    let mut okm = [0u8; 48];
+    ark_std::test_rng().fill_bytes(&mut okm);
+
+    let mut group = c.benchmark_group("from_okm");
+    group.bench_function("montgomery", |b| b.iter(|| Fq::from_okm(black_box(&okm))));
+    group.bench_function("biguint", |b| b.iter(|| from_okm_biguint(black_box(&okm))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_from_okm);
+criterion_main!(benches);
@@ -0,0 +1,24 @@
+use ark_bn254::{Fr, G1Projective, G2Projective};
+use ark_ec::Group;
+use ark_ff::UniformRand;
+use bn254_hash2curve::glv::{mul_gls_g2, mul_gls_g2_ct, mul_glv_g1, mul_glv_g1_ct};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_scalar_mul(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let k = Fr::rand(&mut rng);
+    let p = G1Projective::generator() * Fr::rand(&mut rng);
+    let q = G2Projective::generator() * Fr::rand(&mut rng);
+
+    let mut group = c.benchmark_group("scalar_mul");
+    group.bench_function("g1/arkworks", |b| b.iter(|| black_box(p) * black_box(k)));
+    group.bench_function("g1/mul_glv_g1", |b| b.iter(|| mul_glv_g1(black_box(&p), black_box(&k))));
+    group.bench_function("g1/mul_glv_g1_ct", |b| b.iter(|| mul_glv_g1_ct(black_box(&p), black_box(&k))));
+    group.bench_function("g2/arkworks", |b| b.iter(|| black_box(q) * black_box(k)));
+    group.bench_function("g2/mul_gls_g2", |b| b.iter(|| mul_gls_g2(black_box(&q), black_box(&k))));
+    group.bench_function("g2/mul_gls_g2_ct", |b| b.iter(|| mul_gls_g2_ct(black_box(&q), black_box(&k))));
+    group.finish();
+}
+
+criterion_group!(benches, bench_scalar_mul);
+criterion_main!(benches);
@@ -0,0 +1,31 @@
+use ark_bn254::{fq::Fq, fq2::Fq2};
+use ark_ff::UniformRand;
+use bn254_hash2curve::hash2g1::MapToCurve1;
+use bn254_hash2curve::hash2g2::MapToCurve2;
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_map(c: &mut Criterion) {
+    let mut rng = ark_std::test_rng();
+    let u1: Vec<Fq> = (0..64).map(|_| Fq::rand(&mut rng)).collect();
+    let u2: Vec<Fq2> = (0..64).map(|_| Fq2::rand(&mut rng)).collect();
+
+    let mut group = c.benchmark_group("svdw");
+    group.bench_function("MapToCurve1", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i = (i + 1) % u1.len();
+            MapToCurve1(black_box(u1[i]))
+        })
+    });
+    group.bench_function("MapToCurve2", |b| {
+        let mut i = 0;
+        b.iter(|| {
+            i = (i + 1) % u2.len();
+            MapToCurve2(black_box(u2[i]))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_map);
+criterion_main!(benches);
@@ -0,0 +1,39 @@
+use ark_bn254::{Bn254, Fr, G1Affine};
+use ark_ec::{pairing::Pairing, AffineRepr};
+use bn254_hash2curve::bls::min_pk;
+use bn254_hash2curve::cache::HashToG2Cache;
+use bn254_hash2curve::hash2g2::{HashToG2, HashToG2Prepared};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+// e(pk, H(msg)) * e(-g1, sig) == 1 over a message that is verified again and again
+fn bench_prepared(c: &mut Criterion) {
+    let sk = Fr::from(7u64);
+    let pk = min_pk::sk_to_pk(&sk);
+    let sig = min_pk::sign(&sk, b"msg");
+    let g1 = -G1Affine::generator();
+
+    let mut cache = HashToG2Cache::new(16);
+    // warm the cache
+    let _ = cache.get(b"msg", min_pk::DST);
+
+    let mut group = c.benchmark_group("prepared");
+    group.bench_function("HashToG2", |b| b.iter(|| HashToG2(black_box(b"msg"), min_pk::DST)));
+    group.bench_function("HashToG2Prepared", |b| b.iter(|| HashToG2Prepared(black_box(b"msg"), min_pk::DST)));
+    group.bench_function("verify/uncached", |b| {
+        b.iter(|| {
+            let q = HashToG2(black_box(b"msg"), min_pk::DST);
+            Bn254::multi_pairing([pk, g1], [q, sig])
+        })
+    });
+    group.bench_function("verify/cache_hit", |b| {
+        b.iter(|| {
+            let (_, q) = cache.get(black_box(b"msg"), min_pk::DST);
+            Bn254::multi_pairing([pk, g1], [q, sig.into()])
+        })
+    });
+    group.finish();
+    assert!(cache.hits() > 0);
+}
+
+criterion_group!(benches, bench_prepared);
+criterion_main!(benches);
@@ -0,0 +1,25 @@
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{PrimeField, Zero};
+use bn254_hash2curve::hash2g1::{is_in_g1_subgroup, HashToG1};
+use bn254_hash2curve::hash2g2::{is_in_g2_subgroup, HashToG2};
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+fn bench_subgroup(c: &mut Criterion) {
+    let p1: G1Affine = HashToG1(b"abc", b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_");
+    let p2: G2Affine = HashToG2(b"abc", b"QUUX-V01-CS02-with-BN254G2_XMD:SHA-256_SVDW_RO_");
+
+    let mut group = c.benchmark_group("subgroup");
+    group.bench_function("g1/is_in_g1_subgroup", |b| b.iter(|| is_in_g1_subgroup(black_box(&p1))));
+    group.bench_function("g1/mul_by_r", |b| {
+        b.iter(|| black_box(&p1).mul_bigint(Fr::MODULUS).is_zero())
+    });
+    group.bench_function("g2/is_in_g2_subgroup", |b| b.iter(|| is_in_g2_subgroup(black_box(&p2))));
+    group.bench_function("g2/mul_by_r", |b| {
+        b.iter(|| black_box(&p2).mul_bigint(Fr::MODULUS).is_zero())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_subgroup);
+criterion_main!(benches);
@@ -0,0 +1,89 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine};
+use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_std::rand::Rng;
+use crate::bls::min_sig;
+use crate::glv::mul_glv_g1_ct;
+use crate::hash2g1::{is_in_g1_subgroup, HashToG1_projective};
+
+// Blind BLS signatures (Boldyreva) on top of bls::min_sig: the user sends [r] H(msg) for a random r,
+// the signer answers with [sk] [r] H(msg) without learning msg, and the user removes r to obtain
+// [sk] H(msg), an ordinary min_sig signature. With dst = min_sig::DST the result verifies with
+// min_sig::verify.
+// https://www.iacr.org/archive/pkc2003/25670031/25670031.pdf
+
+// blind returns [r] HashToG1(msg, dst) and the blinding factor r, which the user keeps secret
+pub fn blind<R: Rng + ?Sized>(msg: &[u8], dst: &[u8], rng: &mut R) -> (G1Affine, Fr) {
+    let mut r = Fr::rand(rng);
+    while r.is_zero() {
+        r = Fr::rand(rng);
+    }
+    (mul_glv_g1_ct(&HashToG1_projective(msg, dst), &r).into_affine(), r)
+}
+
+// blind_sign signs a blinded point, None if the point is the identity or not in G1
+pub fn blind_sign(sk: &Fr, blinded: &G1Affine) -> Option<G1Affine> {
+    if blinded.is_zero() || !is_in_g1_subgroup(blinded) {
+        return None;
+    }
+    Some(mul_glv_g1_ct(&blinded.into_group(), sk).into_affine())
+}
+
+// blind_verify lets the user check the signer's answer before unblinding: e(blinded, pk) == e(sig, g2)
+pub fn blind_verify(pk: &G2Affine, blinded: &G1Affine, blind_sig: &G1Affine) -> bool {
+    if !min_sig::key_validate(pk) || blinded.is_zero() || !is_in_g1_subgroup(blind_sig) {
+        return false;
+    }
+    Bn254::multi_pairing([*blinded, -*blind_sig], [*pk, G2Affine::generator()]).is_zero()
+}
+
+// unblind returns [1 / r] blind_sig
+pub fn unblind(blind_sig: &G1Affine, r: &Fr) -> G1Affine {
+    let r_inv = r.inverse().expect("blinding factor must be non-zero");
+    mul_glv_g1_ct(&blind_sig.into_group(), &r_inv).into_affine()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{blind, blind_sign, blind_verify, unblind};
+    use crate::bls::min_sig;
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::UniformRand;
+
+    #[test]
+    fn blind_sign_test() {
+        let mut rng = ark_std::test_rng();
+        for _ in 0..5 {
+            let sk = Fr::rand(&mut rng);
+            let pk = min_sig::sk_to_pk(&sk);
+
+            let (blinded, r) = blind(b"credential", min_sig::DST, &mut rng);
+            let blind_sig = blind_sign(&sk, &blinded).unwrap();
+            assert!(blind_verify(&pk, &blinded, &blind_sig));
+            assert!(!blind_verify(&min_sig::sk_to_pk(&Fr::rand(&mut rng)), &blinded, &blind_sig));
+
+            // the unblinded signature is the ordinary one and verifies as such
+            let sig = unblind(&blind_sig, &r);
+            assert_eq!(sig, min_sig::sign(&sk, b"credential"));
+            assert!(min_sig::verify(&pk, b"credential", &sig));
+            assert!(!min_sig::verify(&pk, b"other credential", &sig));
+
+            // the blinded signature, or one unblinded with the wrong factor, is not a signature on msg
+            assert!(!min_sig::verify(&pk, b"credential", &blind_sig));
+            assert!(!min_sig::verify(&pk, b"credential", &unblind(&blind_sig, &Fr::rand(&mut rng))));
+
+            // two blindings of the same message differ
+            let (blinded2, _) = blind(b"credential", min_sig::DST, &mut rng);
+            assert_ne!(blinded, blinded2);
+        }
+    }
+
+    #[test]
+    fn blind_sign_invalid_test() {
+        assert!(blind_sign(&Fr::from(7u64), &G1Affine::zero()).is_none());
+        let off_curve = G1Affine::new_unchecked(G1Affine::generator().x, G1Affine::generator().x);
+        assert!(blind_sign(&Fr::from(7u64), &off_curve).is_none());
+    }
+}
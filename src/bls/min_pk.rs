@@ -0,0 +1,205 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::Pairing, CurveGroup, Group};
+use ark_ff::Zero;
+use alloc::vec::Vec;
+use crate::glv::{mul_glv_g1_ct, mul_gls_g2_ct};
+use crate::hash2g1::is_in_g1_subgroup;
+use crate::hash2g2::{hash_to_g2_batch, is_in_g2_subgroup, HashToG2, HashToG2_projective};
+use super::Variant;
+
+// min_pk: public keys in G1, signatures in G2
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-ciphersuites
+pub const DST: &[u8] = b"BLS_SIG_BN254G2_XMD:SHA-256_SVDW_RO_NUL_";
+
+pub(crate) struct MinPk;
+
+impl Variant for MinPk {
+    type PublicKey = G1Affine;
+    type Signature = G2Affine;
+
+    fn sk_to_pk(sk: &Fr) -> G1Affine {
+        mul_glv_g1_ct(&G1Projective::generator(), sk).into_affine()
+    }
+
+    fn hash_and_mul(sk: &Fr, msg: &[u8], dst: &[u8]) -> G2Affine {
+        mul_gls_g2_ct(&HashToG2_projective(msg, dst), sk).into_affine()
+    }
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G2Affine {
+        HashToG2(msg, dst)
+    }
+
+    fn hash_batch(msgs: &[&[u8]], dst: &[u8]) -> Vec<G2Affine> {
+        hash_to_g2_batch(msgs, dst)
+    }
+
+    fn pk_in_subgroup(pk: &G1Affine) -> bool {
+        is_in_g1_subgroup(pk)
+    }
+
+    fn sig_in_subgroup(sig: &G2Affine) -> bool {
+        is_in_g2_subgroup(sig)
+    }
+
+    fn pairing_product_is_one(pks: Vec<G1Affine>, sigs: Vec<G2Affine>) -> bool {
+        Bn254::multi_pairing(pks, sigs).is_zero()
+    }
+}
+
+pub fn sk_to_pk(sk: &Fr) -> G1Affine {
+    MinPk::sk_to_pk(sk)
+}
+
+// key_validate rejects the identity and points outside of G1
+pub fn key_validate(pk: &G1Affine) -> bool {
+    super::key_validate::<MinPk>(pk)
+}
+
+pub fn sign(sk: &Fr, msg: &[u8]) -> G2Affine {
+    core_sign(sk, msg, DST)
+}
+
+pub fn verify(pk: &G1Affine, msg: &[u8], sig: &G2Affine) -> bool {
+    core_verify(pk, msg, sig, DST)
+}
+
+// aggregate returns None for an empty list or a signature outside of G2
+pub fn aggregate(sigs: &[G2Affine]) -> Option<G2Affine> {
+    super::aggregate::<MinPk>(sigs)
+}
+
+// aggregate_verify of the basic scheme, the messages must be pairwise distinct
+pub fn aggregate_verify(pks: &[G1Affine], msgs: &[&[u8]], sig: &G2Affine) -> bool {
+    super::aggregate_verify::<MinPk>(pks, msgs, sig, DST)
+}
+
+// fast_aggregate_verify checks a signature aggregated over a single message. It is only sound when
+// rogue public keys are ruled out some other way, e.g. by a proof of possession.
+pub fn fast_aggregate_verify(pks: &[G1Affine], msg: &[u8], sig: &G2Affine) -> bool {
+    super::core_fast_aggregate_verify::<MinPk>(pks, msg, sig, DST)
+}
+
+pub(crate) fn core_sign(sk: &Fr, msg: &[u8], dst: &[u8]) -> G2Affine {
+    super::core_sign::<MinPk>(sk, msg, dst)
+}
+
+pub(crate) fn core_verify(pk: &G1Affine, msg: &[u8], sig: &G2Affine, dst: &[u8]) -> bool {
+    super::core_verify::<MinPk>(pk, msg, sig, dst)
+}
+
+// The proof of possession scheme: every public key comes with a signature over its own encoding under
+// a separate POP tag, which rules out rogue keys and makes fast_aggregate_verify sound.
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-proof-of-possession
+pub mod pop {
+    use ark_bn254::{Fr, G1Affine, G2Affine};
+    use crate::bls;
+    use super::MinPk;
+
+    pub const DST: &[u8] = b"BLS_SIG_BN254G2_XMD:SHA-256_SVDW_RO_POP_";
+    pub const POP_DST: &[u8] = b"BLS_POP_BN254G2_XMD:SHA-256_SVDW_RO_POP_";
+
+    pub fn sign(sk: &Fr, msg: &[u8]) -> G2Affine {
+        bls::core_sign::<MinPk>(sk, msg, DST)
+    }
+
+    pub fn verify(pk: &G1Affine, msg: &[u8], sig: &G2Affine) -> bool {
+        bls::core_verify::<MinPk>(pk, msg, sig, DST)
+    }
+
+    pub fn aggregate_verify(pks: &[G1Affine], msgs: &[&[u8]], sig: &G2Affine) -> bool {
+        bls::core_aggregate_verify::<MinPk>(pks, msgs, sig, DST)
+    }
+
+    // fast_aggregate_verify assumes every key in pks passed pop_verify
+    pub fn fast_aggregate_verify(pks: &[G1Affine], msg: &[u8], sig: &G2Affine) -> bool {
+        bls::core_fast_aggregate_verify::<MinPk>(pks, msg, sig, DST)
+    }
+
+    pub fn pop_prove(sk: &Fr) -> G2Affine {
+        bls::pop_prove::<MinPk>(sk, POP_DST)
+    }
+
+    pub fn pop_verify(pk: &G1Affine, proof: &G2Affine) -> bool {
+        bls::pop_verify::<MinPk>(pk, proof, POP_DST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{aggregate, aggregate_verify, fast_aggregate_verify, key_validate, pop, sign, sk_to_pk, verify};
+    use crate::bls::key_gen;
+    use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+    use std::str::FromStr;
+
+    // the public functions pass the right tags to the shared implementation
+    #[test]
+    fn api_test() {
+        let sks: Vec<_> = (0..2u8).map(|i| key_gen(&[i; 32], b"")).collect();
+        let pks: Vec<G1Affine> = sks.iter().map(sk_to_pk).collect();
+        assert!(pks.iter().all(key_validate) && !key_validate(&G1Affine::zero()));
+
+        let sig = aggregate(&[sign(&sks[0], b"msg 0"), sign(&sks[1], b"msg 1")]).unwrap();
+        assert!(aggregate_verify(&pks, &[b"msg 0", b"msg 1"], &sig));
+        let sig = aggregate(&[sign(&sks[0], b"msg"), sign(&sks[1], b"msg")]).unwrap();
+        assert!(verify(&pks[0], b"msg", &sign(&sks[0], b"msg")));
+        assert!(fast_aggregate_verify(&pks, b"msg", &sig));
+        assert!(!pop::fast_aggregate_verify(&pks, b"msg", &sig));
+
+        let sig = aggregate(&[pop::sign(&sks[0], b"msg"), pop::sign(&sks[1], b"msg")]).unwrap();
+        assert!(pop::verify(&pks[0], b"msg", &pop::sign(&sks[0], b"msg")));
+        assert!(pop::fast_aggregate_verify(&pks, b"msg", &sig));
+        assert!(pop::aggregate_verify(&pks, &[b"msg", b"msg"], &sig));
+        assert!(!fast_aggregate_verify(&pks, b"msg", &sig));
+        assert!(pop::pop_verify(&pks[0], &pop::pop_prove(&sks[0])));
+        assert!(!pop::pop_verify(&pks[1], &pop::pop_prove(&sks[0])));
+        assert!(!pop::verify(&pks[0], b"msg", &G2Affine::zero()));
+    }
+
+    // The scheme itself is tested for both variants in bls/mod.rs. sk = key_gen(0x00..1f, ""), pk and the
+    // signature over "abc" were recomputed with an independent Python implementation (HKDF, SVDW, affine
+    // scalar multiplication, cofactor clearing through ψ). No other BLS library has BN254 vectors for
+    // these ciphersuites, so they are regression vectors only. The signature depends on the hash to G2
+    // flavour.
+    fn check_vector(sig: G2Affine) {
+        let sk = key_gen(&(0..32).collect::<Vec<u8>>(), b"");
+        let pk = G1Affine::new(
+            Fq::from_str("4864384094879778281259039379125437143705547730210178366793951710585605977797").unwrap(),
+            Fq::from_str("17349760835579656848261693432058010767934041649581048682968799004675037170656").unwrap(),
+        );
+        assert_eq!(sk_to_pk(&sk), pk);
+        assert_eq!(sign(&sk, b"abc"), sig);
+        assert!(verify(&pk, b"abc", &sig));
+    }
+
+    #[cfg(all(feature = "constantine_compatible", not(feature = "gnark_crypto_compatible")))]
+    #[test]
+    fn vectors_test() {
+        check_vector(G2Affine::new(
+            Fq2::new(
+                Fq::from_str("10975306019658452106364232878857402616347359142223720268244089564695073836598").unwrap(),
+                Fq::from_str("4534284185251902713001643419572566208091618980841806206382956496671997411082").unwrap(),
+            ),
+            Fq2::new(
+                Fq::from_str("2657399110712966040455468445448307702984309000312083345967115540799601948544").unwrap(),
+                Fq::from_str("8002091338858830412211859489881886401059163589829450341887315987777483916413").unwrap(),
+            ),
+        ));
+    }
+
+    #[cfg(feature = "gnark_crypto_compatible")]
+    #[test]
+    fn vectors_test() {
+        check_vector(G2Affine::new(
+            Fq2::new(
+                Fq::from_str("4795766838289015053405337554709256212059503301073568151106310765466460963689").unwrap(),
+                Fq::from_str("12021787449653459852647156887735725703854290061871763583683812160452006496517").unwrap(),
+            ),
+            Fq2::new(
+                Fq::from_str("7269799551774430060115848747975823349020227676093035881437627272680030146529").unwrap(),
+                Fq::from_str("6893546780504728559238526880110514424170363364479657594330112201569243022523").unwrap(),
+            ),
+        ));
+    }
+}
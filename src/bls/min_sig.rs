@@ -0,0 +1,185 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G2Affine, G2Projective};
+use ark_ec::{pairing::Pairing, CurveGroup, Group};
+use ark_ff::Zero;
+use alloc::vec::Vec;
+use crate::glv::{mul_glv_g1_ct, mul_gls_g2_ct};
+use crate::hash2g1::{hash_to_g1_batch, is_in_g1_subgroup, HashToG1, HashToG1_projective};
+use crate::hash2g2::is_in_g2_subgroup;
+use super::Variant;
+
+// min_sig: signatures in G1, public keys in G2
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-ciphersuites
+pub const DST: &[u8] = b"BLS_SIG_BN254G1_XMD:SHA-256_SVDW_RO_NUL_";
+
+pub(crate) struct MinSig;
+
+impl Variant for MinSig {
+    type PublicKey = G2Affine;
+    type Signature = G1Affine;
+
+    fn sk_to_pk(sk: &Fr) -> G2Affine {
+        mul_gls_g2_ct(&G2Projective::generator(), sk).into_affine()
+    }
+
+    fn hash_and_mul(sk: &Fr, msg: &[u8], dst: &[u8]) -> G1Affine {
+        mul_glv_g1_ct(&HashToG1_projective(msg, dst), sk).into_affine()
+    }
+
+    fn hash(msg: &[u8], dst: &[u8]) -> G1Affine {
+        HashToG1(msg, dst)
+    }
+
+    fn hash_batch(msgs: &[&[u8]], dst: &[u8]) -> Vec<G1Affine> {
+        hash_to_g1_batch(msgs, dst)
+    }
+
+    fn pk_in_subgroup(pk: &G2Affine) -> bool {
+        is_in_g2_subgroup(pk)
+    }
+
+    fn sig_in_subgroup(sig: &G1Affine) -> bool {
+        is_in_g1_subgroup(sig)
+    }
+
+    fn pairing_product_is_one(pks: Vec<G2Affine>, sigs: Vec<G1Affine>) -> bool {
+        Bn254::multi_pairing(sigs, pks).is_zero()
+    }
+}
+
+pub fn sk_to_pk(sk: &Fr) -> G2Affine {
+    MinSig::sk_to_pk(sk)
+}
+
+// key_validate rejects the identity and points outside of G2
+pub fn key_validate(pk: &G2Affine) -> bool {
+    super::key_validate::<MinSig>(pk)
+}
+
+pub fn sign(sk: &Fr, msg: &[u8]) -> G1Affine {
+    core_sign(sk, msg, DST)
+}
+
+pub fn verify(pk: &G2Affine, msg: &[u8], sig: &G1Affine) -> bool {
+    core_verify(pk, msg, sig, DST)
+}
+
+// aggregate returns None for an empty list or a signature outside of G1
+pub fn aggregate(sigs: &[G1Affine]) -> Option<G1Affine> {
+    super::aggregate::<MinSig>(sigs)
+}
+
+// aggregate_verify of the basic scheme, the messages must be pairwise distinct
+pub fn aggregate_verify(pks: &[G2Affine], msgs: &[&[u8]], sig: &G1Affine) -> bool {
+    super::aggregate_verify::<MinSig>(pks, msgs, sig, DST)
+}
+
+// fast_aggregate_verify checks a signature aggregated over a single message. It is only sound when
+// rogue public keys are ruled out some other way, e.g. by a proof of possession.
+pub fn fast_aggregate_verify(pks: &[G2Affine], msg: &[u8], sig: &G1Affine) -> bool {
+    super::core_fast_aggregate_verify::<MinSig>(pks, msg, sig, DST)
+}
+
+pub(crate) fn core_sign(sk: &Fr, msg: &[u8], dst: &[u8]) -> G1Affine {
+    super::core_sign::<MinSig>(sk, msg, dst)
+}
+
+pub(crate) fn core_verify(pk: &G2Affine, msg: &[u8], sig: &G1Affine, dst: &[u8]) -> bool {
+    super::core_verify::<MinSig>(pk, msg, sig, dst)
+}
+
+// The proof of possession scheme: every public key comes with a signature over its own encoding under
+// a separate POP tag, which rules out rogue keys and makes fast_aggregate_verify sound.
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-proof-of-possession
+pub mod pop {
+    use ark_bn254::{Fr, G1Affine, G2Affine};
+    use crate::bls;
+    use super::MinSig;
+
+    pub const DST: &[u8] = b"BLS_SIG_BN254G1_XMD:SHA-256_SVDW_RO_POP_";
+    pub const POP_DST: &[u8] = b"BLS_POP_BN254G1_XMD:SHA-256_SVDW_RO_POP_";
+
+    pub fn sign(sk: &Fr, msg: &[u8]) -> G1Affine {
+        bls::core_sign::<MinSig>(sk, msg, DST)
+    }
+
+    pub fn verify(pk: &G2Affine, msg: &[u8], sig: &G1Affine) -> bool {
+        bls::core_verify::<MinSig>(pk, msg, sig, DST)
+    }
+
+    pub fn aggregate_verify(pks: &[G2Affine], msgs: &[&[u8]], sig: &G1Affine) -> bool {
+        bls::core_aggregate_verify::<MinSig>(pks, msgs, sig, DST)
+    }
+
+    // fast_aggregate_verify assumes every key in pks passed pop_verify
+    pub fn fast_aggregate_verify(pks: &[G2Affine], msg: &[u8], sig: &G1Affine) -> bool {
+        bls::core_fast_aggregate_verify::<MinSig>(pks, msg, sig, DST)
+    }
+
+    pub fn pop_prove(sk: &Fr) -> G1Affine {
+        bls::pop_prove::<MinSig>(sk, POP_DST)
+    }
+
+    pub fn pop_verify(pk: &G2Affine, proof: &G1Affine) -> bool {
+        bls::pop_verify::<MinSig>(pk, proof, POP_DST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{aggregate, aggregate_verify, fast_aggregate_verify, key_validate, pop, sign, sk_to_pk, verify};
+    use crate::bls::key_gen;
+    use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+    use ark_ec::AffineRepr;
+    use std::str::FromStr;
+
+    // the public functions pass the right tags to the shared implementation
+    #[test]
+    fn api_test() {
+        let sks: Vec<_> = (0..2u8).map(|i| key_gen(&[i; 32], b"")).collect();
+        let pks: Vec<G2Affine> = sks.iter().map(sk_to_pk).collect();
+        assert!(pks.iter().all(key_validate) && !key_validate(&G2Affine::zero()));
+
+        let sig = aggregate(&[sign(&sks[0], b"msg 0"), sign(&sks[1], b"msg 1")]).unwrap();
+        assert!(aggregate_verify(&pks, &[b"msg 0", b"msg 1"], &sig));
+        let sig = aggregate(&[sign(&sks[0], b"msg"), sign(&sks[1], b"msg")]).unwrap();
+        assert!(verify(&pks[0], b"msg", &sign(&sks[0], b"msg")));
+        assert!(fast_aggregate_verify(&pks, b"msg", &sig));
+        assert!(!pop::fast_aggregate_verify(&pks, b"msg", &sig));
+
+        let sig = aggregate(&[pop::sign(&sks[0], b"msg"), pop::sign(&sks[1], b"msg")]).unwrap();
+        assert!(pop::verify(&pks[0], b"msg", &pop::sign(&sks[0], b"msg")));
+        assert!(pop::fast_aggregate_verify(&pks, b"msg", &sig));
+        assert!(pop::aggregate_verify(&pks, &[b"msg", b"msg"], &sig));
+        assert!(!fast_aggregate_verify(&pks, b"msg", &sig));
+        assert!(pop::pop_verify(&pks[0], &pop::pop_prove(&sks[0])));
+        assert!(!pop::pop_verify(&pks[1], &pop::pop_prove(&sks[0])));
+        assert!(!pop::verify(&pks[0], b"msg", &G1Affine::zero()));
+    }
+
+    // The scheme itself is tested for both variants in bls/mod.rs. sk = key_gen(0x00..1f, ""), pk and the
+    // signature over "abc" were recomputed with an independent Python implementation (HKDF, SVDW, affine
+    // scalar multiplication). No other BLS library has BN254 vectors for these ciphersuites, so they are
+    // regression vectors only.
+    #[test]
+    fn vectors_test() {
+        let sk = key_gen(&(0..32).collect::<Vec<u8>>(), b"");
+        let pk = G2Affine::new(
+            Fq2::new(
+                Fq::from_str("2202742259710460880647504241906587147782400249655740383000289259145027062607").unwrap(),
+                Fq::from_str("14034186195276665016067825184331948414864433978500412597498844543531883009840").unwrap(),
+            ),
+            Fq2::new(
+                Fq::from_str("2681004996212374934608151274504186351987525439891847647407968280382896306216").unwrap(),
+                Fq::from_str("20349747648840717500436554660458994840262695315265490820695284536332679781550").unwrap(),
+            ),
+        );
+        let sig = G1Affine::new(
+            Fq::from_str("20803573893708671143429662671150459392704196255398122492262051558980394248839").unwrap(),
+            Fq::from_str("8046730809555036588785097038436682550239357279289665829677533509450894459773").unwrap(),
+        );
+        assert_eq!(sk_to_pk(&sk), pk);
+        assert_eq!(sign(&sk, b"abc"), sig);
+        assert!(verify(&pk, b"abc", &sig));
+    }
+}
@@ -0,0 +1,292 @@
+use ark_bn254::Fr;
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::{PrimeField, Zero};
+use ark_serialize::CanonicalSerialize;
+use alloc::vec::Vec;
+use core::ops::Neg;
+use sha2::{Digest, Sha256};
+use crate::hkdf::{hkdf_expand, hkdf_extract};
+
+// BLS signatures over BN254 following the IETF BLS signature draft, with hash_to_curve from this crate.
+// min_pk has public keys in G1 and signatures in G2, min_sig the other way around.
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html
+pub mod min_pk;
+pub mod min_sig;
+
+// L = ceil((3 * ceil(log2(r))) / 16)
+const KEYGEN_L: usize = 48;
+
+// key_gen derives a secret key from at least 32 bytes of keying material, as KeyGen of the draft:
+// HKDF-SHA256 with salt H("BLS-SIG-KEYGEN-SALT-"), 48 bytes of output reduced mod r
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-bls-signature-05.html#name-keygen
+pub fn key_gen(ikm: &[u8], key_info: &[u8]) -> Fr {
+    if ikm.len() < 32 {
+        panic!("IKM must be at least 32 bytes");
+    }
+
+    let mut salt: [u8; 32] = Sha256::digest(b"BLS-SIG-KEYGEN-SALT-").into();
+    loop {
+        let prk = hkdf_extract(&salt, &[ikm, &[0]]);
+        let mut okm = [0u8; KEYGEN_L];
+        hkdf_expand(&prk, &[key_info, &(KEYGEN_L as u16).to_be_bytes()], &mut okm);
+        let sk = Fr::from_be_bytes_mod_order(&okm);
+        if !sk.is_zero() {
+            return sk;
+        }
+        salt = Sha256::digest(salt).into();
+    }
+}
+
+// all_distinct checks that no message appears twice, as required by the basic scheme
+fn all_distinct(msgs: &[&[u8]]) -> bool {
+    let mut sorted = msgs.to_vec();
+    sorted.sort_unstable();
+    sorted.windows(2).all(|w| w[0] != w[1])
+}
+
+// Variant fixes the groups of a ciphersuite: min_pk and min_sig only differ in which of G1, G2 holds
+// the public keys and which one the hashed messages and signatures, everything else is written once below
+pub(crate) trait Variant {
+    type PublicKey: AffineRepr<ScalarField = Fr> + Neg<Output = Self::PublicKey>;
+    type Signature: AffineRepr<ScalarField = Fr>;
+
+    // [sk]g in constant time
+    fn sk_to_pk(sk: &Fr) -> Self::PublicKey;
+    // [sk]H(msg) in constant time
+    fn hash_and_mul(sk: &Fr, msg: &[u8], dst: &[u8]) -> Self::Signature;
+    fn hash(msg: &[u8], dst: &[u8]) -> Self::Signature;
+    fn hash_batch(msgs: &[&[u8]], dst: &[u8]) -> Vec<Self::Signature>;
+    fn pk_in_subgroup(pk: &Self::PublicKey) -> bool;
+    fn sig_in_subgroup(sig: &Self::Signature) -> bool;
+    // e(pk_1, sig_1) * ... * e(pk_n, sig_n) == 1, with the arguments of each pairing in (G1, G2) order
+    fn pairing_product_is_one(pks: Vec<Self::PublicKey>, sigs: Vec<Self::Signature>) -> bool;
+}
+
+// key_validate rejects the identity and points outside of the public key subgroup
+pub(crate) fn key_validate<V: Variant>(pk: &V::PublicKey) -> bool {
+    !pk.is_zero() && V::pk_in_subgroup(pk)
+}
+
+// aggregate returns None for an empty list or a signature outside of the subgroup
+pub(crate) fn aggregate<V: Variant>(sigs: &[V::Signature]) -> Option<V::Signature> {
+    if sigs.is_empty() || !sigs.iter().all(V::sig_in_subgroup) {
+        return None;
+    }
+    Some(sigs.iter().fold(<V::Signature as AffineRepr>::Group::zero(), |acc, s| acc + s).into_affine())
+}
+
+// aggregate_verify of the basic scheme, the messages must be pairwise distinct
+pub(crate) fn aggregate_verify<V: Variant>(pks: &[V::PublicKey], msgs: &[&[u8]], sig: &V::Signature, dst: &[u8]) -> bool {
+    all_distinct(msgs) && core_aggregate_verify::<V>(pks, msgs, sig, dst)
+}
+
+pub(crate) fn core_sign<V: Variant>(sk: &Fr, msg: &[u8], dst: &[u8]) -> V::Signature {
+    V::hash_and_mul(sk, msg, dst)
+}
+
+// e(pk, H(msg)) == e(g, sig)
+pub(crate) fn core_verify<V: Variant>(pk: &V::PublicKey, msg: &[u8], sig: &V::Signature, dst: &[u8]) -> bool {
+    if !key_validate::<V>(pk) || !V::sig_in_subgroup(sig) {
+        return false;
+    }
+    V::pairing_product_is_one(alloc::vec![*pk, -V::PublicKey::generator()], alloc::vec![V::hash(msg, dst), *sig])
+}
+
+// e(pk_1, H(msg_1)) * ... * e(pk_n, H(msg_n)) == e(g, sig)
+pub(crate) fn core_aggregate_verify<V: Variant>(pks: &[V::PublicKey], msgs: &[&[u8]], sig: &V::Signature, dst: &[u8]) -> bool {
+    if pks.is_empty() || pks.len() != msgs.len() || !pks.iter().all(key_validate::<V>) || !V::sig_in_subgroup(sig) {
+        return false;
+    }
+    let mut a = pks.to_vec();
+    let mut b = V::hash_batch(msgs, dst);
+    a.push(-V::PublicKey::generator());
+    b.push(*sig);
+    V::pairing_product_is_one(a, b)
+}
+
+// core_fast_aggregate_verify checks a signature aggregated over a single message. It is only sound when
+// rogue public keys are ruled out some other way, e.g. by a proof of possession.
+pub(crate) fn core_fast_aggregate_verify<V: Variant>(pks: &[V::PublicKey], msg: &[u8], sig: &V::Signature, dst: &[u8]) -> bool {
+    if pks.is_empty() || !pks.iter().all(key_validate::<V>) {
+        return false;
+    }
+    let pk = pks.iter().fold(<V::PublicKey as AffineRepr>::Group::zero(), |acc, pk| acc + pk).into_affine();
+    core_verify::<V>(&pk, msg, sig, dst)
+}
+
+// the compressed arkworks encoding of the public key is the message signed by a proof of possession
+fn pk_to_octets<P: CanonicalSerialize>(pk: &P) -> Vec<u8> {
+    let mut out = Vec::new();
+    pk.serialize_compressed(&mut out).unwrap();
+    out
+}
+
+pub(crate) fn pop_prove<V: Variant>(sk: &Fr, dst: &[u8]) -> V::Signature {
+    core_sign::<V>(sk, &pk_to_octets(&V::sk_to_pk(sk)), dst)
+}
+
+pub(crate) fn pop_verify<V: Variant>(pk: &V::PublicKey, proof: &V::Signature, dst: &[u8]) -> bool {
+    core_verify::<V>(pk, &pk_to_octets(pk), proof, dst)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{aggregate, aggregate_verify, core_fast_aggregate_verify, core_sign, core_verify, key_gen, key_validate};
+    use super::{min_pk, min_sig, pop_prove, pop_verify, Variant};
+    use super::min_pk::MinPk;
+    use super::min_sig::MinSig;
+    use ark_bn254::Fr;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_serialize::CanonicalSerialize;
+    use ark_ff::UniformRand;
+    use std::str::FromStr;
+
+    // the tag of the basic scheme, and the signature and proof tags of the proof of possession scheme
+    struct Tags {
+        dst: &'static [u8],
+        pop_sig_dst: &'static [u8],
+        pop_dst: &'static [u8],
+    }
+
+    const MIN_PK: Tags = Tags { dst: min_pk::DST, pop_sig_dst: min_pk::pop::DST, pop_dst: min_pk::pop::POP_DST };
+    const MIN_SIG: Tags = Tags { dst: min_sig::DST, pop_sig_dst: min_sig::pop::DST, pop_dst: min_sig::pop::POP_DST };
+
+    fn keys<V: Variant>(n: u8) -> Vec<(Fr, V::PublicKey)> {
+        (0..n).map(|i| {
+            let sk = key_gen(&[i; 32], b"");
+            (sk, V::sk_to_pk(&sk))
+        }).collect()
+    }
+
+    fn sign_verify<V: Variant>(t: &Tags) {
+        let mut rng = ark_std::test_rng();
+        for (sk, pk) in keys::<V>(4) {
+            assert_eq!(pk, (V::PublicKey::generator() * sk).into_affine());
+            let sig = core_sign::<V>(&sk, b"message", t.dst);
+            assert!(core_verify::<V>(&pk, b"message", &sig, t.dst));
+            assert!(!core_verify::<V>(&pk, b"other message", &sig, t.dst));
+            assert!(!core_verify::<V>(&V::sk_to_pk(&Fr::rand(&mut rng)), b"message", &sig, t.dst));
+            assert!(!core_verify::<V>(&pk, b"message", &V::Signature::zero(), t.dst));
+        }
+        assert!(!key_validate::<V>(&V::PublicKey::zero()));
+        assert!(!core_verify::<V>(&V::PublicKey::zero(), b"message", &V::Signature::zero(), t.dst));
+    }
+
+    fn aggregate_verify_all<V: Variant>(t: &Tags) {
+        let keys = keys::<V>(4);
+        let pks: Vec<V::PublicKey> = keys.iter().map(|(_, pk)| *pk).collect();
+        let msgs: Vec<&[u8]> = vec![b"msg 0", b"msg 1", b"msg 2", b"msg 3"];
+
+        let sigs: Vec<V::Signature> = keys.iter().zip(&msgs).map(|((sk, _), m)| core_sign::<V>(sk, m, t.dst)).collect();
+        let sig = aggregate::<V>(&sigs).unwrap();
+        assert!(aggregate_verify::<V>(&pks, &msgs, &sig, t.dst));
+        assert!(!aggregate_verify::<V>(&pks[1..], &msgs[1..], &sig, t.dst));
+        assert!(!aggregate_verify::<V>(&pks, &[b"msg 0", b"msg 1", b"msg 2", b"msg 4"], &sig, t.dst));
+        assert!(!aggregate_verify::<V>(&[], &[], &sig, t.dst));
+        assert!(aggregate::<V>(&[]).is_none());
+
+        // repeated messages are rejected
+        let sigs: Vec<V::Signature> = keys.iter().map(|(sk, _)| core_sign::<V>(sk, b"msg", t.dst)).collect();
+        let sig = aggregate::<V>(&sigs).unwrap();
+        assert!(!aggregate_verify::<V>(&pks, &[&b"msg"[..]; 4], &sig, t.dst));
+
+        assert!(core_fast_aggregate_verify::<V>(&pks, b"msg", &sig, t.dst));
+        assert!(!core_fast_aggregate_verify::<V>(&pks[1..], b"msg", &sig, t.dst));
+        assert!(!core_fast_aggregate_verify::<V>(&pks, b"msg 0", &sig, t.dst));
+        assert!(!core_fast_aggregate_verify::<V>(&[], b"msg", &sig, t.dst));
+    }
+
+    fn pop<V: Variant>(t: &Tags) {
+        let keys = keys::<V>(4);
+        let pks: Vec<V::PublicKey> = keys.iter().map(|(_, pk)| *pk).collect();
+        for (sk, pk) in &keys {
+            let proof = pop_prove::<V>(sk, t.pop_dst);
+            assert!(pop_verify::<V>(pk, &proof, t.pop_dst));
+            assert!(!pop_verify::<V>(&pks[0], &pop_prove::<V>(&(*sk + Fr::from(1u64)), t.pop_dst), t.pop_dst));
+
+            // the proof is not a signature over the encoded key under either signature tag
+            let mut encoded = Vec::new();
+            pk.serialize_compressed(&mut encoded).unwrap();
+            assert!(!core_verify::<V>(pk, &encoded, &proof, t.pop_sig_dst));
+            assert!(!core_verify::<V>(pk, &encoded, &proof, t.dst));
+
+            // signatures of the two schemes are not interchangeable
+            let pop_sig = core_sign::<V>(sk, b"msg", t.pop_sig_dst);
+            assert!(core_verify::<V>(pk, b"msg", &pop_sig, t.pop_sig_dst));
+            assert!(!core_verify::<V>(pk, b"msg", &pop_sig, t.dst));
+            assert!(!core_verify::<V>(pk, b"msg", &core_sign::<V>(sk, b"msg", t.dst), t.pop_sig_dst));
+        }
+
+        let sigs: Vec<V::Signature> = keys.iter().map(|(sk, _)| core_sign::<V>(sk, b"msg", t.pop_sig_dst)).collect();
+        let sig = aggregate::<V>(&sigs).unwrap();
+        assert!(core_fast_aggregate_verify::<V>(&pks, b"msg", &sig, t.pop_sig_dst));
+        assert!(!core_fast_aggregate_verify::<V>(&pks[1..], b"msg", &sig, t.pop_sig_dst));
+        assert!(super::core_aggregate_verify::<V>(&pks, &[&b"msg"[..]; 4], &sig, t.pop_sig_dst));
+    }
+
+    fn rogue_key<V: Variant>(t: &Tags) {
+        let mut rng = ark_std::test_rng();
+        let (sk_victim, victim) = keys::<V>(1)[0];
+
+        // pk_rogue = [a]g - pk_victim makes the aggregate key [a]g, so the attacker alone can produce
+        // a signature that verifies as signed by both
+        let a = Fr::rand(&mut rng);
+        let rogue = (V::PublicKey::generator() * a - victim).into_affine();
+        let forged = core_sign::<V>(&a, b"msg", t.pop_sig_dst);
+        assert!(core_fast_aggregate_verify::<V>(&[victim, rogue], b"msg", &forged, t.pop_sig_dst));
+
+        // without knowing the secret key of pk_rogue no proof of possession verifies, so the key is
+        // never admitted
+        let attempts = [pop_prove::<V>(&a, t.pop_dst), pop_prove::<V>(&Fr::rand(&mut rng), t.pop_dst), forged, V::Signature::zero()];
+        for proof in attempts {
+            assert!(!pop_verify::<V>(&rogue, &proof, t.pop_dst));
+        }
+        let admitted: Vec<V::PublicKey> = [(victim, pop_prove::<V>(&sk_victim, t.pop_dst)), (rogue, attempts[0])]
+            .iter()
+            .filter(|(pk, proof)| pop_verify::<V>(pk, proof, t.pop_dst))
+            .map(|(pk, _)| *pk)
+            .collect();
+        assert_eq!(admitted, vec![victim]);
+        assert!(!core_fast_aggregate_verify::<V>(&admitted, b"msg", &forged, t.pop_sig_dst));
+    }
+
+    #[test]
+    fn sign_verify_test() {
+        sign_verify::<MinPk>(&MIN_PK);
+        sign_verify::<MinSig>(&MIN_SIG);
+    }
+
+    #[test]
+    fn aggregate_test() {
+        aggregate_verify_all::<MinPk>(&MIN_PK);
+        aggregate_verify_all::<MinSig>(&MIN_SIG);
+    }
+
+    #[test]
+    fn pop_test() {
+        pop::<MinPk>(&MIN_PK);
+        pop::<MinSig>(&MIN_SIG);
+    }
+
+    #[test]
+    fn rogue_key_test() {
+        rogue_key::<MinPk>(&MIN_PK);
+        rogue_key::<MinSig>(&MIN_SIG);
+    }
+
+    #[test]
+    fn key_gen_test() {
+        let ikm: Vec<u8> = (0..32).collect();
+        // cross-checked with an independent Python HKDF (hmac, hashlib), no other BLS library involved
+        assert_eq!(key_gen(&ikm, b""), Fr::from_str("16064802052022166991147556124334728874230695198385765080017367623540346798705").unwrap());
+        assert_eq!(key_gen(&ikm, b"key info"), Fr::from_str("6836419591280169696248678781188960776325958113563000559202749442599750393182").unwrap());
+        assert_ne!(key_gen(&ikm, b""), key_gen(&[0u8; 32], b""));
+    }
+
+    #[test]
+    #[should_panic]
+    fn key_gen_short_ikm_test() {
+        key_gen(&[0u8; 31], b"");
+    }
+}
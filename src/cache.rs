@@ -0,0 +1,137 @@
+use ark_bn254::G2Affine;
+use alloc::{collections::BTreeMap, vec::Vec};
+use crate::hash2g2::{G2Prepared, HashToG2Prepared};
+
+// HashToG2Cache memoizes HashToG2Prepared for verifiers that pair the same message point many times.
+// It keeps the capacity most recently used (msg, dst) pairs: every entry carries the tick of its last
+// use and a second map ordered by tick finds the least recently used entry to evict.
+
+// dst is at most 255 bytes, I2OSP(len(dst), 1) || dst || msg identifies (msg, dst)
+type Key = Vec<u8>;
+
+struct Entry {
+    point: G2Affine,
+    prepared: G2Prepared,
+    last_used: u64,
+}
+
+pub struct HashToG2Cache {
+    capacity: usize,
+    tick: u64,
+    entries: BTreeMap<Key, Entry>,
+    recency: BTreeMap<u64, Key>,
+    hits: u64,
+    misses: u64,
+}
+
+impl HashToG2Cache {
+    pub fn new(capacity: usize) -> Self {
+        if capacity == 0 {
+            panic!("cache capacity must be non-zero");
+        }
+        HashToG2Cache { capacity, tick: 0, entries: BTreeMap::new(), recency: BTreeMap::new(), hits: 0, misses: 0 }
+    }
+
+    // get returns HashToG2Prepared(msg, dst), from the cache when possible
+    pub fn get(&mut self, msg: &[u8], dst: &[u8]) -> (G2Affine, G2Prepared) {
+        if dst.len() > 255 {
+            panic!("dst size is invalid");
+        }
+        let key: Key = [&[dst.len() as u8][..], dst, msg].concat();
+        self.tick += 1;
+
+        if let Some(entry) = self.entries.get_mut(&key) {
+            self.hits += 1;
+            self.recency.remove(&entry.last_used);
+            entry.last_used = self.tick;
+            self.recency.insert(self.tick, key);
+            return (entry.point, entry.prepared.clone());
+        }
+
+        self.misses += 1;
+        if self.entries.len() == self.capacity {
+            let (_, lru) = self.recency.pop_first().unwrap();
+            self.entries.remove(&lru);
+        }
+        let (point, prepared) = HashToG2Prepared(msg, dst);
+        self.entries.insert(key.clone(), Entry { point, prepared: prepared.clone(), last_used: self.tick });
+        self.recency.insert(self.tick, key);
+        (point, prepared)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn hits(&self) -> u64 {
+        self.hits
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.recency.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::HashToG2Cache;
+    use crate::bls::min_pk;
+    use crate::hash2g2::{HashToG2, HashToG2Prepared};
+    use ark_bn254::{Bn254, G1Affine};
+    use ark_ec::{pairing::Pairing, AffineRepr};
+    use ark_ff::Zero;
+
+    #[test]
+    fn prepared_test() {
+        let sk = ark_bn254::Fr::from(7u64);
+        let pk = min_pk::sk_to_pk(&sk);
+        let sig = min_pk::sign(&sk, b"msg");
+        let (q, prepared) = HashToG2Prepared(b"msg", min_pk::DST);
+        assert_eq!(q, HashToG2(b"msg", min_pk::DST));
+
+        // the prepared point pairs like the affine one
+        let affine = Bn254::multi_pairing([pk, -G1Affine::generator()], [q, sig]);
+        let with_prepared = Bn254::multi_pairing([pk, -G1Affine::generator()], [prepared, sig.into()]);
+        assert_eq!(affine, with_prepared);
+        assert!(with_prepared.is_zero());
+    }
+
+    #[test]
+    fn lru_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G2_XMD:SHA-256_SVDW_RO_";
+        let mut cache = HashToG2Cache::new(2);
+        assert!(cache.is_empty());
+
+        assert_eq!(cache.get(b"a", dst).0, HashToG2(b"a", dst));
+        assert_eq!(cache.get(b"b", dst).0, HashToG2(b"b", dst));
+        assert_eq!(cache.get(b"a", dst).0, HashToG2(b"a", dst));
+        assert_eq!((cache.hits(), cache.misses()), (1, 2));
+
+        // b is the least recently used and goes first
+        assert_eq!(cache.get(b"c", dst).0, HashToG2(b"c", dst));
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(b"a", dst).0, HashToG2(b"a", dst));
+        assert_eq!((cache.hits(), cache.misses()), (2, 3));
+        assert_eq!(cache.get(b"b", dst).0, HashToG2(b"b", dst));
+        assert_eq!((cache.hits(), cache.misses()), (2, 4));
+
+        // the key covers the dst and is not ambiguous between msg and dst
+        assert_eq!(cache.get(b"a", b"other dst").0, HashToG2(b"a", b"other dst"));
+        assert_eq!(cache.get(b"ab", b"c").0, HashToG2(b"ab", b"c"));
+        assert_eq!(cache.get(b"b", b"ca").0, HashToG2(b"b", b"ca"));
+        assert_eq!(cache.misses(), 7);
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
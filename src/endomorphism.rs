@@ -0,0 +1,136 @@
+use ark_bn254::{fq::Fq, fq2::Fq2, G2Projective};
+use ark_ff::MontFp;
+
+// The untwist-Frobenius-twist endomorphism ψ = u o π o u⁻¹ of E'(Fq2), u: E' → E the isomorphism from
+// the twist to E over Fq12 and π the p-power Frobenius. ψ, its powers and its inverse only conjugate the
+// coordinates and scale x and y, so they are applied to Jacobian coordinates directly: conjugation
+// commutes with the division by Z², so no normalization is needed.
+//
+// ψ satisfies ψ² - [t]ψ + [p] = 0 on E'(Fq2), t = 6x² + 1 the trace of Frobenius, and acts as
+// multiplication by p = 6x² mod r on G2.
+// https://eprint.iacr.org/2008/117.pdf
+
+// ψ(x, y) = (conj(x) * ENDO_U, conj(y) * ENDO_V)
+pub const ENDO_U: Fq2 = Fq2::new(
+    MontFp!("21575463638280843010398324269430826099269044274347216827212613867836435027261"),
+    MontFp!("10307601595873709700152284273816112264069230130616436755625194854815875713954"),
+);
+pub const ENDO_V: Fq2 = Fq2::new(
+    MontFp!("2821565182194536844548159561693502659359617185244120367078079554186484126554"),
+    MontFp!("3505843767911556378687030309984248845540243509899259641013678093033130930403"),
+);
+
+// ψ²(x, y) = (x * ENDO_U * conj(ENDO_U), y * ENDO_V * conj(ENDO_V)) = (x * ω, -y), ω a cube root of unity in Fq
+pub const PSI2_X: Fq = MontFp!("21888242871839275220042445260109153167277707414472061641714758635765020556616");
+
+// ψ³(x, y) = (conj(x) * ω * ENDO_U, -conj(y) * ENDO_V)
+pub const PSI3_X: Fq2 = Fq2::new(
+    MontFp!("3772000881919853776433695186713858239009073593817195771773381919316419345261"),
+    MontFp!("2236595495967245188281701248203181795121068902605861227855261137820944008926"),
+);
+pub const PSI3_Y: Fq2 = Fq2::new(
+    MontFp!("19066677689644738377698246183563772429336693972053703295610958340458742082029"),
+    MontFp!("18382399103927718843559375435273026243156067647398564021675359801612095278180"),
+);
+
+// ψ⁻¹(x, y) = (conj(x) * conj(ENDO_U⁻¹), conj(y) * conj(ENDO_V⁻¹)), conj(ENDO_V⁻¹) = PSI3_Y
+pub const PSI_INV_X: Fq2 = Fq2::new(
+    MontFp!("18429021223477853657660792034369865839114504446431234726392080002137598044644"),
+    MontFp!("9344045779998320333812420223237981029506012124075525679208581902008406485703"),
+);
+pub const PSI_INV_Y: Fq2 = PSI3_Y;
+
+pub fn conjugate(a: &Fq2) -> Fq2 {
+    Fq2::new(a.c0, -a.c1)
+}
+
+// conj(p) * (cx, cy, 1)
+fn conjugate_and_scale(p: &G2Projective, cx: &Fq2, cy: &Fq2) -> G2Projective {
+    let mut res = *p;
+    res.x = conjugate(&p.x) * cx;
+    res.y = conjugate(&p.y) * cy;
+    res.z = conjugate(&p.z);
+    res
+}
+
+pub fn psi(p: &G2Projective) -> G2Projective {
+    conjugate_and_scale(p, &ENDO_U, &ENDO_V)
+}
+
+// ψ² involves no conjugation and ω is in Fq
+pub fn psi2(p: &G2Projective) -> G2Projective {
+    let mut res = *p;
+    res.x.mul_assign_by_basefield(&PSI2_X);
+    res.y = -res.y;
+    res
+}
+
+pub fn psi3(p: &G2Projective) -> G2Projective {
+    conjugate_and_scale(p, &PSI3_X, &PSI3_Y)
+}
+
+pub fn psi_inv(p: &G2Projective) -> G2Projective {
+    conjugate_and_scale(p, &PSI_INV_X, &PSI_INV_Y)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::random_twist_point;
+    use super::{psi, psi2, psi3, psi_inv, ENDO_U, ENDO_V, PSI2_X, PSI3_X, PSI3_Y, PSI_INV_X, PSI_INV_Y, conjugate};
+    use ark_bn254::{Fq, Fq2, Fr, G2Projective};
+    use ark_ec::{CurveGroup, Group};
+    use ark_ff::{BigInteger, Field, PrimeField, UniformRand, Zero};
+
+    // t = 6x² + 1
+    const TRACE: [u64; 2] = [0xf83e9682e87cfd47, 0x6f4d8248eeb859fb];
+
+    #[test]
+    fn constants_test() {
+        assert_eq!(PSI2_X, (ENDO_U * conjugate(&ENDO_U)).c0);
+        assert_eq!(PSI2_X.pow([3]), Fq::ONE);
+        assert_eq!(-Fq2::ONE, ENDO_V * conjugate(&ENDO_V));
+        assert_eq!(PSI3_X, ENDO_U * Fq2::new(PSI2_X, Fq::ZERO));
+        assert_eq!(PSI3_Y, -ENDO_V);
+        assert_eq!(PSI_INV_X, conjugate(&ENDO_U.inverse().unwrap()));
+        assert_eq!(PSI_INV_Y, conjugate(&ENDO_V.inverse().unwrap()));
+    }
+
+    #[test]
+    fn psi_powers_test() {
+        let mut rng = ark_std::test_rng();
+        for _ in 0..20 {
+            let p = random_twist_point(&mut rng);
+            assert!(psi(&p).into_affine().is_on_curve());
+            assert_eq!(psi2(&p), psi(&psi(&p)));
+            assert_eq!(psi3(&p), psi(&psi(&psi(&p))));
+            assert_eq!(psi_inv(&psi(&p)), p);
+            assert_eq!(psi(&psi_inv(&p)), p);
+            assert_eq!(psi3(&psi(&p)), psi2(&psi2(&p)));
+            assert!(psi(&G2Projective::zero()).is_zero());
+        }
+    }
+
+    #[test]
+    fn psi_characteristic_polynomial_test() {
+        let mut rng = ark_std::test_rng();
+        for _ in 0..20 {
+            // ψ² - [t]ψ + [p] = 0 on the whole twist
+            let p = random_twist_point(&mut rng);
+            let res = psi2(&p) - psi(&p).mul_bigint(TRACE) + p.mul_bigint(Fq::MODULUS);
+            assert!(res.is_zero());
+        }
+    }
+
+    #[test]
+    fn psi_eigenvalue_test() {
+        let mut rng = ark_std::test_rng();
+        let p_mod_r = Fr::from_le_bytes_mod_order(&Fq::MODULUS.to_bytes_le()).into_bigint();
+        for _ in 0..20 {
+            // ψ = [p mod r] on the r-torsion
+            let q = G2Projective::generator() * Fr::rand(&mut rng);
+            assert_eq!(psi(&q), q.mul_bigint(p_mod_r));
+            assert_eq!(psi(&q), q.mul_bigint(Fq::MODULUS));
+        }
+    }
+}
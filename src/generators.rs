@@ -0,0 +1,234 @@
+use ark_bn254::{Fq, Fq2, G1Affine, G2Affine};
+use ark_ec::AffineRepr;
+use ark_ff::{BigInteger, PrimeField, Zero};
+use alloc::vec::Vec;
+use core::ops::Range;
+use crate::hash2g1::hash_to_g1_batch;
+use crate::hash2g2::hash_to_g2_batch;
+
+// Nothing-up-my-sleeve generators for Pedersen commitments, IPA and the like. The i-th generator of a
+// label is HashToG1(label || I2OSP(i, 4), GENERATORS_G1_DST), resp. HashToG2 with GENERATORS_G2_DST.
+// The index is always 4 big-endian bytes, so generators of different labels never share an input, and
+// any index range can be derived on its own.
+
+pub const GENERATORS_G1_DST: &[u8] = b"BN254G1_XMD:SHA-256_SVDW_RO_GENERATORS_";
+pub const GENERATORS_G2_DST: &[u8] = b"BN254G2_XMD:SHA-256_SVDW_RO_GENERATORS_";
+
+fn generator_inputs(label: &[u8], range: Range<u32>) -> Vec<Vec<u8>> {
+    range.map(|i| [label, &i.to_be_bytes()].concat()).collect()
+}
+
+// derive_generators_g1 returns the generators of indices 0..n
+pub fn derive_generators_g1(label: &[u8], n: u32) -> Vec<G1Affine> {
+    derive_generators_g1_range(label, 0..n)
+}
+
+pub fn derive_generators_g1_range(label: &[u8], range: Range<u32>) -> Vec<G1Affine> {
+    let inputs = generator_inputs(label, range);
+    let msgs: Vec<&[u8]> = inputs.iter().map(|m| &m[..]).collect();
+    hash_to_g1_batch(&msgs, GENERATORS_G1_DST)
+}
+
+// derive_generators_g2 returns the generators of indices 0..n. They depend on the SVDW constants of
+// the enabled compatibility feature, like HashToG2.
+pub fn derive_generators_g2(label: &[u8], n: u32) -> Vec<G2Affine> {
+    derive_generators_g2_range(label, 0..n)
+}
+
+pub fn derive_generators_g2_range(label: &[u8], range: Range<u32>) -> Vec<G2Affine> {
+    let inputs = generator_inputs(label, range);
+    let msgs: Vec<&[u8]> = inputs.iter().map(|m| &m[..]).collect();
+    hash_to_g2_batch(&msgs, GENERATORS_G2_DST)
+}
+
+// Encoding of the derived points for other stacks
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Encoding {
+    // the precompile format of EIP-196/197: big-endian x || y, Fq2 elements as c1 || c0, zero for the identity
+    Ethereum,
+    // gnark-crypto's compressed format: big-endian x with the two top bits 0b10 if y is the smallest
+    // of ±y, 0b11 if it is the largest and 0b01 for the identity
+    Gnark,
+}
+
+fn fq_to_be(x: &Fq, out: &mut Vec<u8>) {
+    out.extend_from_slice(&x.into_bigint().to_bytes_be());
+}
+
+// y > (p - 1) / 2
+fn fq_lexicographically_largest(y: &Fq) -> bool {
+    y.into_bigint() > Fq::MODULUS_MINUS_ONE_DIV_TWO
+}
+
+// gnark compares the imaginary part first, the real part if the imaginary part is zero
+fn fq2_lexicographically_largest(y: &Fq2) -> bool {
+    if y.c1.is_zero() {
+        fq_lexicographically_largest(&y.c0)
+    } else {
+        fq_lexicographically_largest(&y.c1)
+    }
+}
+
+const GNARK_COMPRESSED_SMALLEST: u8 = 0b10 << 6;
+const GNARK_COMPRESSED_LARGEST: u8 = 0b11 << 6;
+const GNARK_COMPRESSED_INFINITY: u8 = 0b01 << 6;
+
+fn gnark_flag(is_zero: bool, largest: bool) -> u8 {
+    if is_zero {
+        GNARK_COMPRESSED_INFINITY
+    } else if largest {
+        GNARK_COMPRESSED_LARGEST
+    } else {
+        GNARK_COMPRESSED_SMALLEST
+    }
+}
+
+// encode_g1 returns 64 bytes for Ethereum and 32 bytes for Gnark
+pub fn encode_g1(p: &G1Affine, encoding: Encoding) -> Vec<u8> {
+    let mut out = Vec::new();
+    match encoding {
+        Encoding::Ethereum => {
+            if p.is_zero() {
+                out.resize(64, 0);
+            } else {
+                fq_to_be(&p.x, &mut out);
+                fq_to_be(&p.y, &mut out);
+            }
+        }
+        Encoding::Gnark => {
+            if p.is_zero() {
+                out.resize(32, 0);
+            } else {
+                fq_to_be(&p.x, &mut out);
+            }
+            out[0] |= gnark_flag(p.is_zero(), !p.is_zero() && fq_lexicographically_largest(&p.y));
+        }
+    }
+    out
+}
+
+// encode_g2 returns 128 bytes for Ethereum and 64 bytes for Gnark
+pub fn encode_g2(p: &G2Affine, encoding: Encoding) -> Vec<u8> {
+    let mut out = Vec::new();
+    match encoding {
+        Encoding::Ethereum => {
+            if p.is_zero() {
+                out.resize(128, 0);
+            } else {
+                for c in [p.x.c1, p.x.c0, p.y.c1, p.y.c0] {
+                    fq_to_be(&c, &mut out);
+                }
+            }
+        }
+        Encoding::Gnark => {
+            if p.is_zero() {
+                out.resize(64, 0);
+            } else {
+                fq_to_be(&p.x.c1, &mut out);
+                fq_to_be(&p.x.c0, &mut out);
+            }
+            out[0] |= gnark_flag(p.is_zero(), !p.is_zero() && fq2_lexicographically_largest(&p.y));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::hex_to_bytes;
+    use super::{derive_generators_g1, derive_generators_g1_range, derive_generators_g2, derive_generators_g2_range, encode_g1, encode_g2, Encoding, GENERATORS_G1_DST, GENERATORS_G2_DST};
+    use crate::hash2g1::HashToG1;
+    use crate::hash2g2::{is_in_g2_subgroup, HashToG2};
+    use ark_bn254::{Fq, G1Affine, G1Projective, G2Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::{Field, PrimeField, Zero};
+    use std::collections::HashSet;
+    use std::str::FromStr;
+
+    #[test]
+    fn derive_generators_test() {
+        let g1 = derive_generators_g1(b"pedersen", 64);
+        let g2 = derive_generators_g2(b"pedersen", 8);
+        assert_eq!(g1[5], HashToG1(b"pedersen\x00\x00\x00\x05", GENERATORS_G1_DST));
+        assert_eq!(g2[5], HashToG2(b"pedersen\x00\x00\x00\x05", GENERATORS_G2_DST));
+        assert!(g2.iter().all(is_in_g2_subgroup));
+
+        // a range is a slice of the full list
+        assert_eq!(derive_generators_g1_range(b"pedersen", 10..20), g1[10..20]);
+        assert_eq!(derive_generators_g2_range(b"pedersen", 3..8), g2[3..8]);
+        assert!(derive_generators_g1_range(b"pedersen", 5..5).is_empty());
+
+        // labels are separated
+        assert_ne!(derive_generators_g1(b"pedersen2", 1)[0], g1[0]);
+        assert_ne!(derive_generators_g1(b"", 1)[0], g1[0]);
+
+        // pairwise distinct, also up to sign, and never the identity or the standard generator
+        let mut seen = HashSet::new();
+        for p in &g1 {
+            assert!(!p.is_zero());
+            assert_ne!(*p, G1Affine::generator());
+            assert!(seen.insert(p.x));
+        }
+        let mut seen = HashSet::new();
+        for p in &g2 {
+            assert!(!p.is_zero());
+            assert!(seen.insert(p.x));
+        }
+    }
+
+    #[test]
+    fn no_small_relations_test() {
+        // no a_0 g_0 + ... + a_4 g_4 = 0 with |a_i| <= 2 and some a_i != 0
+        let g: Vec<G1Projective> = derive_generators_g1(b"ipa", 5).iter().map(|p| p.into_group()).collect();
+        let mut sums = vec![G1Projective::zero()];
+        for p in &g {
+            sums = sums.iter().flat_map(|s| (-2i64..=2).map(move |a| *s + *p * ark_bn254::Fr::from(a))).collect();
+        }
+        assert_eq!(sums.len(), 3125);
+        assert_eq!(sums.iter().filter(|s| s.is_zero()).count(), 1);
+    }
+
+    // generated by this implementation
+    #[test]
+    fn vectors_test() {
+        let g = derive_generators_g1(b"pedersen", 1)[0];
+        assert_eq!(g.x, Fq::from_str("3512305717148092082417875767554447495072209285564058926858875408327039953998").unwrap());
+        assert_eq!(g.y, Fq::from_str("5242946177311999190963931722210053827293411757733595071317002678792637461710").unwrap());
+    }
+
+    #[test]
+    fn encoding_test() {
+        // the standard generators, gnark-crypto's g1GenAff and g2GenAff
+        let g1 = G1Affine::generator();
+        let eth = encode_g1(&g1, Encoding::Ethereum);
+        assert_eq!(eth[31], 1);
+        assert_eq!(eth[63], 2);
+        assert_eq!(eth.len(), 64);
+        let gnark = encode_g1(&g1, Encoding::Gnark);
+        assert_eq!(gnark, hex_to_bytes("8000000000000000000000000000000000000000000000000000000000000001"));
+        assert_eq!(encode_g1(&-g1, Encoding::Gnark)[0], 0xc0);
+        assert_eq!(encode_g1(&G1Affine::zero(), Encoding::Gnark)[0], 0x40);
+        assert_eq!(encode_g1(&G1Affine::zero(), Encoding::Ethereum), vec![0u8; 64]);
+
+        let g2 = G2Affine::generator();
+        let eth = encode_g2(&g2, Encoding::Ethereum);
+        assert_eq!(eth[..32], hex_to_bytes("198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2"));
+        assert_eq!(eth[32..64], hex_to_bytes("1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"));
+        assert_eq!(eth[64..96], hex_to_bytes("090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b"));
+        assert_eq!(eth[96..], hex_to_bytes("12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"));
+        let gnark = encode_g2(&g2, Encoding::Gnark);
+        assert_eq!(gnark.len(), 64);
+        assert_eq!(gnark[0] & 0xc0, 0x80);
+        assert_eq!(gnark[0] & 0x3f, eth[0]);
+        assert_eq!(gnark[1..], eth[1..64]);
+        assert_eq!(encode_g2(&-g2, Encoding::Gnark)[0] & 0xc0, 0xc0);
+
+        // the flag picks y among ±y
+        for p in derive_generators_g1(b"flags", 16) {
+            let largest = encode_g1(&p, Encoding::Gnark)[0] & 0xc0 == 0xc0;
+            assert_eq!(largest, p.y.into_bigint() > (-p.y).into_bigint());
+            assert_eq!(p.y.square(), p.x.pow([3]) + Fq::from(3u64));
+        }
+    }
+}
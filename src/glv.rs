@@ -0,0 +1,331 @@
+use ark_bn254::{fq::Fq, fr::Fr, G1Projective, G2Projective};
+use ark_ec::{short_weierstrass::{Projective, SWCurveConfig}, Group};
+use ark_ff::{MontFp, PrimeField, Zero};
+use alloc::vec;
+use crate::endomorphism;
+
+// Scalar multiplication using the endomorphisms of BN254: on G1 φ(x, y) = (βx, y) acts as [λ], on G2
+// ψ acts as [p] = [6x²]. k is split into short scalars (Babai rounding against a reduced basis of the
+// lattice of (k_0, ..., k_{d-1}) with Σ k_i λ^i = 0 mod r), and the short multiplications share their
+// doublings: 127 bit halves on G1, 65 bit quarters on G2.
+// https://www.iacr.org/archive/crypto2001/21390189.pdf (GLV), https://eprint.iacr.org/2008/117.pdf (GLS)
+//
+// The _ct variants recode the short scalars as in https://eprint.iacr.org/2013/158.pdf so that every
+// step is one doubling and one addition of a table entry picked by a masked scan, with no branch on
+// the scalar. The additions themselves are arkworks' Jacobian formulas, which only branch on the
+// identity and on equal inputs, and the recoding keeps both away from the accumulator except for
+// negligibly many scalars.
+//
+// The inputs must be in the r-torsion: the endomorphisms act as λ and p there only.
+
+const X: i128 = 4965661367192848881;
+
+// β = ω, the cube root of unity ψ² uses, and λ with φ(P) = [λ]P
+pub const BETA: Fq = endomorphism::PSI2_X;
+pub const LAMBDA: Fr = MontFp!("21888242871839275217838484774961031246154997185409878258781734729429964517155");
+
+// reduced bases, rows are lattice vectors
+const G1_BASIS: [[i128; 2]; 2] = [
+    [2 * X + 1, 6 * X * X + 4 * X + 1],
+    [6 * X * X + 2 * X, -2 * X - 1],
+];
+const G2_BASIS: [[i128; 4]; 4] = [
+    [X + 1, X, X, -2 * X],
+    [2 * X + 1, -X, -(X + 1), -X],
+    [2 * X, 2 * X + 1, 2 * X + 1, 2 * X + 1],
+    [X - 1, 4 * X + 2, -2 * X + 1, X - 1],
+];
+
+// row 0 of the inverse basis as (round(|b| * 2^256), b < 0), so that
+// (k, 0, ..) = Σ round(k * b_j) v_j + (k_0, .., k_{d-1})
+const G1_ROUND: [([u64; 4], bool); 2] = [
+    ([0xd91d232ec7e0b3d7, 0x2, 0, 0], false),
+    ([0x5398fd0300ff6565, 0x4ccef014a773d2d2, 0x2, 0], false),
+];
+const G2_ROUND: [([u64; 4], bool); 4] = [
+    ([0xd0cb46fd51906254, 0xc444fab18d269b9d, 0, 0], false),
+    ([0x001378f5ee78976e, 0x22df9f942d7d77c7, 0x3d00631561b25729, 0x1], false),
+    ([0x36510546a93478ab, 0x916fcfca16bebbe4, 0x9e80318ab0d92b94, 0], false),
+    ([0xf7ae23ce89afae7d, 0xc444fab18d269b9a, 0, 0], true),
+];
+
+// number of digits of the recoded scalars, one more than the bound on |k_i|
+const G1_DIGITS: usize = 128;
+const G2_DIGITS: usize = 66;
+
+// bits 256 to 383 of k * g + 2^255, i.e. round(k * g / 2^256) mod 2^128
+fn round_mul_shift(k: &[u64; 4], g: &[u64; 4]) -> u128 {
+    let mut t = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u128;
+        for j in 0..4 {
+            let v = (k[i] as u128) * (g[j] as u128) + (t[i + j] as u128) + carry;
+            t[i + j] = v as u64;
+            carry = v >> 64;
+        }
+        t[i + 4] = carry as u64;
+    }
+    let mut carry = 1u64 << 63;
+    for limb in t.iter_mut().skip(3) {
+        let (v, c) = limb.overflowing_add(carry);
+        *limb = v;
+        carry = c as u64;
+    }
+    (t[4] as u128) | ((t[5] as u128) << 64)
+}
+
+// The short scalars are exact integers below 2^127 in absolute value, so all the arithmetic can be
+// done modulo 2^128
+fn decompose<const D: usize>(k: &Fr, basis: &[[i128; D]; D], round: &[([u64; 4], bool); D]) -> [i128; D] {
+    let k = k.into_bigint().0;
+    let mut res = [0i128; D];
+    res[0] = ((k[0] as u128) | ((k[1] as u128) << 64)) as i128;
+    for (v, (g, neg)) in basis.iter().zip(round) {
+        let mut alpha = round_mul_shift(&k, g) as i128;
+        if *neg {
+            alpha = alpha.wrapping_neg();
+        }
+        for (r, v) in res.iter_mut().zip(v) {
+            *r = r.wrapping_sub(alpha.wrapping_mul(*v));
+        }
+    }
+    res
+}
+
+pub fn decompose_g1(k: &Fr) -> [i128; 2] {
+    decompose(k, &G1_BASIS, &G1_ROUND)
+}
+
+pub fn decompose_g2(k: &Fr) -> [i128; 4] {
+    decompose(k, &G2_BASIS, &G2_ROUND)
+}
+
+// φ(x, y) = (βx, y), in Jacobian coordinates as well
+pub fn phi(p: &G1Projective) -> G1Projective {
+    let mut res = *p;
+    res.x *= BETA;
+    res
+}
+
+// Straus-Shamir over all the subset sums of the bases
+fn mul_multi<P: SWCurveConfig, const D: usize>(mut bases: [Projective<P>; D], k: [i128; D]) -> Projective<P> {
+    for (b, k) in bases.iter_mut().zip(k) {
+        if k < 0 {
+            *b = -*b;
+        }
+    }
+    let k = k.map(|k| k.unsigned_abs());
+
+    let mut table = vec![Projective::<P>::zero(); 1 << D];
+    for (i, b) in bases.iter().enumerate() {
+        for mask in 0..(1 << i) {
+            table[mask | (1 << i)] = table[mask] + b;
+        }
+    }
+
+    let bits = k.iter().map(|k| 128 - k.leading_zeros()).max().unwrap();
+    let mut res = Projective::<P>::zero();
+    for i in (0..bits).rev() {
+        res.double_in_place();
+        let mask = k.iter().enumerate().fold(0, |m, (j, k)| m | ((((k >> i) & 1) as usize) << j));
+        if mask != 0 {
+            res += table[mask];
+        }
+    }
+    res
+}
+
+pub fn mul_glv_g1(p: &G1Projective, k: &Fr) -> G1Projective {
+    mul_multi([*p, phi(p)], decompose_g1(k))
+}
+
+pub fn mul_gls_g2(q: &G2Projective, k: &Fr) -> G2Projective {
+    let bases = [*q, endomorphism::psi(q), endomorphism::psi2(q), endomorphism::psi3(q)];
+    mul_multi(bases, decompose_g2(k))
+}
+
+// Selection without branches on the Montgomery limbs, choice is 0 or 1
+trait CtSelect: Copy {
+    fn ct_select(a: &Self, b: &Self, choice: u64) -> Self;
+}
+
+impl CtSelect for Fq {
+    fn ct_select(a: &Fq, b: &Fq, choice: u64) -> Fq {
+        let mask = choice.wrapping_neg();
+        let mut res = *a;
+        for (r, b) in res.0 .0.iter_mut().zip(b.0 .0) {
+            *r ^= mask & (*r ^ b);
+        }
+        res
+    }
+}
+
+impl CtSelect for ark_bn254::Fq2 {
+    fn ct_select(a: &Self, b: &Self, choice: u64) -> Self {
+        Self::new(Fq::ct_select(&a.c0, &b.c0, choice), Fq::ct_select(&a.c1, &b.c1, choice))
+    }
+}
+
+fn ct_select_point<P: SWCurveConfig>(a: &Projective<P>, b: &Projective<P>, choice: u64) -> Projective<P>
+where
+    P::BaseField: CtSelect,
+{
+    Projective::new_unchecked(
+        P::BaseField::ct_select(&a.x, &b.x, choice),
+        P::BaseField::ct_select(&a.y, &b.y, choice),
+        P::BaseField::ct_select(&a.z, &b.z, choice),
+    )
+}
+
+fn ct_neg_point<P: SWCurveConfig>(a: &Projective<P>, choice: u64) -> Projective<P>
+where
+    P::BaseField: CtSelect,
+{
+    let mut res = *a;
+    res.y = P::BaseField::ct_select(&a.y, &-a.y, choice);
+    res
+}
+
+// GLV-SAC: with k_0 odd, k_0 = Σ s_i 2^i with s_i = ±1, and every other k_j = Σ s_i u_ij 2^i with u_ij
+// in {0, 1}. Each column is then s_i (b_0 + Σ u_ij b_j), never the identity.
+fn mul_multi_ct<P: SWCurveConfig, const D: usize, const L: usize>(mut bases: [Projective<P>; D], k: [i128; D]) -> Projective<P>
+where
+    P::BaseField: CtSelect,
+{
+    for (b, k) in bases.iter_mut().zip(k) {
+        *b = ct_neg_point(b, (k as u128 >> 127) as u64);
+    }
+    let mut k = k.map(|k| k.unsigned_abs());
+
+    // k_0 even: recode k_0 + 1 and subtract b_0 at the end
+    let even = ((k[0] & 1) ^ 1) as u64;
+    k[0] += even as u128;
+
+    // s_i = 2 k_0[i + 1] - 1 as a bit (1 for +1), and the column masks
+    let mut sign = [0u64; L];
+    let mut column = [0usize; L];
+    for (i, s) in sign.iter_mut().enumerate().take(L - 1) {
+        *s = ((k[0] >> (i + 1)) & 1) as u64;
+    }
+    sign[L - 1] = 1;
+    for (j, kj) in k.iter().enumerate().skip(1) {
+        // k_j stays non-negative and, as it starts below 2^(L - 1), reaches 0 after the last digit
+        let mut kj = *kj as i128;
+        for (c, s) in column.iter_mut().zip(sign) {
+            let bit = (kj & 1) as usize;
+            *c |= bit << (j - 1);
+            // k_j = floor(k_j / 2) - floor(b_ij / 2), b_ij = bit * s_i and floor(-1 / 2) = -1
+            kj = (kj >> 1) + (bit as i128 & (s as i128 ^ 1));
+        }
+    }
+
+    let mut table = vec![bases[0]; 1 << (D - 1)];
+    for j in 1..D {
+        for mask in 0..(1 << (j - 1)) {
+            table[mask | (1 << (j - 1))] = table[mask] + bases[j];
+        }
+    }
+
+    let lookup = |i: usize| {
+        let mut t = table[0];
+        for (m, entry) in table.iter().enumerate().skip(1) {
+            t = ct_select_point(&t, entry, (m == column[i]) as u64);
+        }
+        ct_neg_point(&t, sign[i] ^ 1)
+    };
+
+    let mut res = lookup(L - 1);
+    for i in (0..L - 1).rev() {
+        res.double_in_place();
+        res += lookup(i);
+    }
+
+    let corrected = res - bases[0];
+    ct_select_point(&res, &corrected, even)
+}
+
+pub fn mul_glv_g1_ct(p: &G1Projective, k: &Fr) -> G1Projective {
+    mul_multi_ct::<_, 2, G1_DIGITS>([*p, phi(p)], decompose_g1(k))
+}
+
+pub fn mul_gls_g2_ct(q: &G2Projective, k: &Fr) -> G2Projective {
+    let bases = [*q, endomorphism::psi(q), endomorphism::psi2(q), endomorphism::psi3(q)];
+    mul_multi_ct::<_, 4, G2_DIGITS>(bases, decompose_g2(k))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{decompose_g1, decompose_g2, mul_gls_g2, mul_gls_g2_ct, mul_glv_g1, mul_glv_g1_ct, phi, LAMBDA};
+    use ark_bn254::{Fr, G1Projective, G2Projective};
+    use ark_ec::Group;
+    use ark_ff::{Field, UniformRand, Zero};
+
+    fn from_i128(k: i128) -> Fr {
+        let f = Fr::from(k.unsigned_abs());
+        if k < 0 { -f } else { f }
+    }
+
+    fn edge_scalars() -> Vec<Fr> {
+        let mut k = vec![Fr::zero(), Fr::ONE, Fr::from(2u64), -Fr::ONE, -Fr::from(2u64), LAMBDA, -LAMBDA];
+        k.push(Fr::from(4965661367192848881u64));
+        k.push(Fr::from(u128::MAX));
+        k
+    }
+
+    #[test]
+    fn decompose_test() {
+        let mut rng = ark_std::test_rng();
+        let psi_eigenvalue = Fr::from(4965661367192848881u64).square() * Fr::from(6u64);
+        let mut scalars = edge_scalars();
+        scalars.extend((0..1000).map(|_| Fr::rand(&mut rng)));
+        for k in scalars {
+            let [k0, k1] = decompose_g1(&k);
+            assert_eq!(from_i128(k0) + from_i128(k1) * LAMBDA, k);
+            assert!(k0.unsigned_abs() < 1 << 127 && k1.unsigned_abs() < 1 << 127);
+
+            let k4 = decompose_g2(&k);
+            let mut sum = Fr::zero();
+            for ki in k4.iter().rev() {
+                sum = sum * psi_eigenvalue + from_i128(*ki);
+            }
+            assert_eq!(sum, k);
+            assert!(k4.iter().all(|k| k.unsigned_abs() < 1 << 65));
+        }
+    }
+
+    #[test]
+    fn phi_test() {
+        let mut rng = ark_std::test_rng();
+        let p = G1Projective::generator() * Fr::rand(&mut rng);
+        assert_eq!(phi(&p), p * LAMBDA);
+    }
+
+    #[test]
+    fn mul_glv_g1_test() {
+        let mut rng = ark_std::test_rng();
+        let mut scalars = edge_scalars();
+        scalars.extend((0..50).map(|_| Fr::rand(&mut rng)));
+        for k in scalars {
+            let p = G1Projective::generator() * Fr::rand(&mut rng);
+            let expected = p * k;
+            assert_eq!(mul_glv_g1(&p, &k), expected);
+            assert_eq!(mul_glv_g1_ct(&p, &k), expected);
+        }
+        assert!(mul_glv_g1(&G1Projective::zero(), &Fr::rand(&mut rng)).is_zero());
+    }
+
+    #[test]
+    fn mul_gls_g2_test() {
+        let mut rng = ark_std::test_rng();
+        let mut scalars = edge_scalars();
+        scalars.extend((0..50).map(|_| Fr::rand(&mut rng)));
+        for k in scalars {
+            let q = G2Projective::generator() * Fr::rand(&mut rng);
+            let expected = q * k;
+            assert_eq!(mul_gls_g2(&q, &k), expected);
+            assert_eq!(mul_gls_g2_ct(&q, &k), expected);
+        }
+        assert!(mul_gls_g2(&G2Projective::zero(), &Fr::rand(&mut rng)).is_zero());
+    }
+}
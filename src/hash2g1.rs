@@ -1,13 +1,14 @@
 
-use ark_bn254::{fq::Fq, G1Affine as G1};
-use ark_ff::Field;
-use num_bigint::BigUint;
-use digest::generic_array::GenericArray;
-use num_integer::Integer;
-use digest::generic_array::{typenum::U48, typenum::U32};
+use ark_bn254::{fq::Fq, Fr, G1Affine as G1, G1Projective};
+use ark_ec::CurveGroup;
+use ark_ff::{batch_inversion, BigInteger, BigInteger256, Field, Fp256, MontBackend, MontConfig, MontFp, PrimeField};
 pub use sha2::{Sha256, digest::Digest};
-use subtle::{Choice, ConditionallySelectable};
-use std::str::FromStr;
+use alloc::{vec, vec::Vec};
+use crate::mimc::hash_to_field_mimc;
+use crate::multibuf::expand_message_batch;
+use crate::sqrt::SqrtRatio;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 pub trait FromOkm<const L: usize>: Sized {
     /// Convert a byte sequence into a scalar
@@ -17,15 +18,41 @@ pub trait FromOkm<const L: usize>: Sized {
 const L: usize = 48;
 impl FromOkm<L> for Fq {
     fn from_okm(data: &[u8; L]) -> Self {
-        let p = BigUint::from_bytes_be(
-            &hex::decode("30644E72E131A029B85045B68181585D97816A916871CA8D3C208C16D87CFD47")
-                .unwrap(),
-        );
+        from_okm_48(data)
+    }
+}
+
+// 48 bytes are enough for Fr as well, r < p
+impl FromOkm<L> for Fr {
+    fn from_okm(data: &[u8; L]) -> Self {
+        from_okm_48(data)
+    }
+}
+
+// Reduces a 48 byte big-endian okm without big integer division.
+// The okm is split as hi·2^256 + lo, with hi the 16 leading bytes and lo the 32 trailing bytes.
+// 2^256 mod p is the Montgomery constant R, and the element whose Montgomery form is R² has value R.
+pub(crate) fn from_okm_48<P: MontConfig<4>>(data: &[u8; 48]) -> Fp256<MontBackend<P, 4>> {
+    let mut hi = [0u64; 4];
+    let mut lo = [0u64; 4];
+    for i in 0..2 {
+        hi[1 - i] = u64::from_be_bytes(data[8 * i..8 * (i + 1)].try_into().unwrap());
+    }
+    for i in 0..4 {
+        lo[3 - i] = u64::from_be_bytes(data[16 + 8 * i..16 + 8 * (i + 1)].try_into().unwrap());
+    }
 
-        let mut x = BigUint::from_bytes_be(&data[..]);
-            x = x.mod_floor(&p);
-            Fq::from(x)
+    // lo < 2^256, a few conditional subtractions bring it below the modulus
+    let mut lo = BigInteger256::new(lo);
+    while lo >= P::MODULUS {
+        lo.sub_with_borrow(&P::MODULUS);
     }
+
+    let r = Fp256::<MontBackend<P, 4>>::new_unchecked(P::R2);
+    let hi = Fp256::<MontBackend<P, 4>>::from_bigint(BigInteger256::new(hi)).unwrap();
+    let lo = Fp256::<MontBackend<P, 4>>::from_bigint(lo).unwrap();
+
+    hi * r + lo
 }
 
 
@@ -38,69 +65,89 @@ pub trait ExpandMsgSHA256 {
 #[allow(non_snake_case)]
 impl ExpandMsgSHA256 for Fq {
     fn expand_message(msg: &[u8], dst: &[u8], LEN_IN_BYTES: usize) -> Vec<u8> {
-        
-        let b_in_bytes: usize = 32;
-        let ell = (LEN_IN_BYTES + b_in_bytes - 1 )/ b_in_bytes;
+        let mut buf = vec![0u8; LEN_IN_BYTES];
+        expand_message_into(msg, dst, &mut buf);
+        buf
+    }
+}
 
-        if ell > 255 {
-            panic!("ell was too big in expand_message_xmd");
-        }
+// expand_message_into implements expand_message_xmd with SHA-256, writing out.len() bytes into out
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-hash-to-curve-16.html#name-expand_message_xmd
+pub fn expand_message_into(msg: &[u8], dst: &[u8], out: &mut [u8]) {
 
-        if dst.len() > 255 {
-            panic!("dst size is invalid");
-        }
+    let len_in_bytes = out.len();
+    let b_in_bytes: usize = 32;
+    let ell = len_in_bytes.div_ceil(b_in_bytes);
 
-        let b_0 = Sha256::new()
-            .chain_update([0u8; 64])    // s_in_bytes for sha256 = 64
-            .chain_update(msg)
-            .chain_update([(LEN_IN_BYTES >> 8) as u8, LEN_IN_BYTES as u8, 0u8])
-            .chain_update(dst)
-            .chain_update([dst.len() as u8])
-            .finalize();
-
-        let mut b_vals = Sha256::new()
-            .chain_update(&b_0[..])
-            .chain_update([1u8])
-            .chain_update(dst)
-            .chain_update([dst.len() as u8])
-            .finalize();
+    if ell > 255 {
+        panic!("ell was too big in expand_message_xmd");
+    }
 
-        let mut buf = vec![0u8; LEN_IN_BYTES];
-        let mut offset = 0;
-
-        for i in 1..ell {
-            // b_0 XOR b_(idx - 1)
-            let mut tmp = GenericArray::<u8, U32>::default();
-            b_0.iter()
-                .zip(&b_vals[..])
-                .enumerate()
-                .for_each(|(j, (b0val, bi1val))| tmp[j] = b0val ^ bi1val);
-            for b in b_vals {
-                buf[offset % LEN_IN_BYTES].conditional_assign(
-                    &b,
-                    Choice::from(if offset < LEN_IN_BYTES { 1 } else { 0 }),
-                );
-                offset += 1;
+    if dst.len() > 255 {
+        panic!("dst size is invalid");
+    }
+
+    let b_0 = Sha256::new()
+        .chain_update([0u8; 64])    // s_in_bytes for sha256 = 64
+        .chain_update(msg)
+        .chain_update([(len_in_bytes >> 8) as u8, len_in_bytes as u8, 0u8])
+        .chain_update(dst)
+        .chain_update([dst.len() as u8])
+        .finalize();
+
+    let mut b_i = Sha256::new()
+        .chain_update(b_0)
+        .chain_update([1u8])
+        .chain_update(dst)
+        .chain_update([dst.len() as u8])
+        .finalize();
+
+    for (i, chunk) in out.chunks_mut(b_in_bytes).enumerate() {
+        if i > 0 {
+            // b_0 XOR b_(i - 1)
+            let mut tmp = [0u8; 32];
+            for (j, t) in tmp.iter_mut().enumerate() {
+                *t = b_0[j] ^ b_i[j];
             }
-            b_vals = Sha256::new()
+            b_i = Sha256::new()
                 .chain_update(tmp)
                 .chain_update([(i + 1) as u8])
                 .chain_update(dst)
                 .chain_update([dst.len() as u8])
                 .finalize();
         }
-        for b in b_vals {
-            buf[offset % LEN_IN_BYTES]
-            .conditional_assign(&b, Choice::from(if offset < LEN_IN_BYTES { 1 } else { 0 }));
-            offset += 1;
-        }
-        buf.into()
+        chunk.copy_from_slice(&b_i[..chunk.len()]);
     }
 }
 
 
+// Expander selects how hash_to_field turns msg into field elements
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Expander {
+    // expand_message_xmd with SHA-256, as specified by the hash-to-curve draft and used by gnark-crypto
+    #[default]
+    XmdSha256,
+    // MiMC with gnark-crypto's BN254 parameters, cheap to recompute inside a gnark circuit (see mimc)
+    MiMC,
+}
+
 pub trait Hash2FieldBN254 {
     fn hash_to_field (msg: &[u8], dst: &[u8], count: usize) -> Vec<Self> where Self: Sized;
+
+    // hash_to_field_with is hash_to_field with the given expander. MiMC digests live in Fr, and r < p,
+    // so they are embedded as integers without reduction.
+    fn hash_to_field_with(msg: &[u8], dst: &[u8], count: usize, expander: Expander) -> Vec<Self>
+    where
+        Self: PrimeField<BigInt = BigInteger256>,
+    {
+        match expander {
+            Expander::XmdSha256 => Self::hash_to_field(msg, dst, count),
+            Expander::MiMC => hash_to_field_mimc(msg, dst, count)
+                .iter()
+                .map(|h| Self::from_bigint(h.into_bigint()).unwrap())
+                .collect(),
+        }
+    }
 }
 
 impl Hash2FieldBN254 for Fq {
@@ -116,136 +163,389 @@ impl Hash2FieldBN254 for Fq {
 
         let len_per_elm = 48;
         let len_in_bytes = count * len_per_elm;
-        // let len_in_bytes = count * len_per_elm;
         let pseudo_random_bytes = Fq::expand_message(msg, dst, len_in_bytes);
-    
-        let mut r = Vec::<Fq>::with_capacity(count);
-        for i in 0..count {
-            let bytes = GenericArray::<u8, U48>::from_slice(
-                &pseudo_random_bytes[i * len_per_elm..(i + 1) * len_per_elm],
-            );
 
-            let x: [u8; 48] = bytes.as_slice().try_into().expect("Wrong length");
-            r.push(Fq::from_okm(&x));
-        }
+        pseudo_random_bytes
+            .chunks_exact(len_per_elm)
+            .map(|x| Fq::from_okm(x.try_into().unwrap()))
+            .collect()
+    }
+}
 
-        r
+// hash_to_field over the scalar field, used to derive scalars (challenges, nonces) from a transcript
+impl Hash2FieldBN254 for Fr {
+
+    fn hash_to_field(msg: &[u8], dst: &[u8], count: usize) -> Vec<Fr> {
+        let mut okm = vec![[0u8; L]; count];
+        expand_message_into(msg, dst, okm.as_flattened_mut());
+        okm.iter().map(Fr::from_okm).collect()
     }
 }
 
+// hash_to_field returns exactly N elements of Fq without heap allocations, the okm lives on the stack.
+pub fn hash_to_field<const N: usize>(msg: &[u8], dst: &[u8]) -> [Fq; N] {
+    let mut okm = [[0u8; L]; N];
+    expand_message_into(msg, dst, okm.as_flattened_mut());
+    okm.map(|x| Fq::from_okm(&x))
+}
+
+// hash_to_field_batch returns hash_to_field::<N>(msg, dst) for every message, the SHA-256
+// computations of the expansions run side by side in SIMD lanes (see multibuf).
+pub fn hash_to_field_batch<const N: usize>(msgs: &[&[u8]], dst: &[u8]) -> Vec<[Fq; N]> {
+    let mut okm = vec![[[0u8; L]; N]; msgs.len()];
+    expand_message_batch(msgs, dst, N * L, okm.as_flattened_mut().as_flattened_mut());
+    okm.iter().map(|x| x.map(|x| Fq::from_okm(&x))).collect()
+}
+
+
 
 
+//constants
+//c1 = g(Z)
+//c2 = -Z / 2
+//c3 = sqrt(-g(Z) * (3 * Z² + 4 * A))     # sgn0(c3) MUST equal 0
+//c4 = -4 * g(Z) / (3 * Z² + 4 * A)
+
+// Z := fp.Element{15230403791020821917, 754611498739239741, 7381016538464732716, 1011752739694698287}
+// c1 := fp.Element{1248766071674976557, 10548065924188627562, 16242874202584236114, 560012691975822483}
+// c2 := fp.Element{12997850613838968789, 14304628359724097447, 2950087706404981016, 1237622763554136189}
+// c3 := fp.Element{8972444824031832946, 5898165201680709844, 10690697896010808308, 824354360198587078}
+// c4 := fp.Element{12077013577332951089, 1872782865047492001, 13514471836495169457, 415649166299893576}
+// (gnark's Montgomery limbs, the values below are the same elements in canonical form)
+
+const Z: Fq = MontFp!("1");
+const C1: Fq = MontFp!("4");
+const C2: Fq = MontFp!("10944121435919637611123202872628637544348155578648911831344518947322613104291");
+const C3: Fq = MontFp!("8815841940592487685674414971303048083897117035520822607866");
+const C4: Fq = MontFp!("7296080957279758407415468581752425029565437052432607887563012631548408736189");
+const B: Fq = MontFp!("3");
 
 // https://github.com/ConsenSys/gnark-crypto/blob/master/ecc/bn254/hash_to_g1.go
 #[allow(non_snake_case)]
 pub fn MapToCurve1(u: Fq) -> G1{
+    MapToCurve1_projective(u).into_affine()
+}
 
-	//constants
-	//c1 = g(Z)
-	//c2 = -Z / 2
-	//c3 = sqrt(-g(Z) * (3 * Z² + 4 * A))     # sgn0(c3) MUST equal 0
-	//c4 = -4 * g(Z) / (3 * Z² + 4 * A)
-
-	// Z := fp.Element{15230403791020821917, 754611498739239741, 7381016538464732716, 1011752739694698287}
-	// c1 := fp.Element{1248766071674976557, 10548065924188627562, 16242874202584236114, 560012691975822483}
-	// c2 := fp.Element{12997850613838968789, 14304628359724097447, 2950087706404981016, 1237622763554136189}
-	// c3 := fp.Element{8972444824031832946, 5898165201680709844, 10690697896010808308, 824354360198587078}
-	// c4 := fp.Element{12077013577332951089, 1872782865047492001, 13514471836495169457, 415649166299893576}
-
-    let mut z: Fq = Fq::from_str("6350874878119819312338956282401532409788428879151445726012394534686998597021").unwrap();
-    let mut c1: Fq = Fq::from_str("3515256640640002027109419384348854550457404359307959241360540244102768179501").unwrap();
-    let mut c2: Fq = Fq::from_str("7768683996859727954953724731427871339453941139073188968338321679979113805781").unwrap();
-    let mut c3: Fq = Fq::from_str("5174556184976127869173189452163337195348491024958816448391141365979064675186").unwrap();
-    let mut c4: Fq = Fq::from_str("2609072103093089037936242735953952295622231240021995565748958972744717830193").unwrap();
-    z = z * Fq::from(Fq::R).inverse().unwrap();
-    c1 = c1 * Fq::from(Fq::R).inverse().unwrap();
-    c2 = c2 * Fq::from(Fq::R).inverse().unwrap();
-    c3 = c3 * Fq::from(Fq::R).inverse().unwrap();
-    c4 = c4 * Fq::from(Fq::R).inverse().unwrap();
+// MapToCurve1_projective is MapToCurve1 with the result left in Jacobian coordinates (Z = 1)
+#[allow(non_snake_case)]
+pub fn MapToCurve1_projective(u: Fq) -> G1Projective {
+    let (tv1, tv2) = svdw_tv(u);
+    let tv3 = (tv1 * tv2).inverse().unwrap_or(Fq::ZERO);   //    5.  tv3 = tv1 * tv2, 6.  tv3 = inv0(tv3)
+    svdw_finish(u, tv1, tv2, tv3)
+}
 
+// Steps 1-4 of the SVDW map, tv1 * tv2 is the only value the map inverts
+fn svdw_tv(u: Fq) -> (Fq, Fq) {
     let mut tv1: Fq = u.square();       //    1.  tv1 = u²
-    tv1 = tv1 * c1;                     //    2.  tv1 = tv1 * c1
+    tv1 *= C1;                          //    2.  tv1 = tv1 * c1
     let tv2: Fq = Fq::ONE + tv1;        //    3.  tv2 = 1 + tv1
     tv1 = Fq::ONE - tv1;                //    4.  tv1 = 1 - tv1
-    let mut tv3: Fq = tv1 * tv2;        //    5.  tv3 = tv1 * tv2 
-    
-    tv3 = tv3.inverse().unwrap();       //    6.  tv3 = inv0(tv3)
+    (tv1, tv2)
+}
+
+// Steps 7-33 of the SVDW map, given tv3 = inv0(tv1 * tv2)
+fn svdw_finish(u: Fq, tv1: Fq, tv2: Fq, tv3: Fq) -> G1Projective {
+
     let mut tv4: Fq = u * tv1;          //    7.  tv4 = u * tv1  
-    tv4 = tv4 * tv3;                    //    8.  tv4 = tv4 * tv3
-    tv4 = tv4 * c3;                     //    9.  tv4 = tv4 * c3
-    let x1: Fq = c2 - tv4;              //    10.  x1 = c2 - tv4
+    tv4 *= tv3;                         //    8.  tv4 = tv4 * tv3
+    tv4 *= C3;                          //    9.  tv4 = tv4 * c3
+    let x1: Fq = C2 - tv4;              //    10.  x1 = c2 - tv4
     
     let mut gx1: Fq = x1.square();      //    11. gx1 = x1²
     //12. gx1 = gx1 + A  It is crucial to include this step if the curve has nonzero A coefficient.
-    gx1 = gx1 * x1;                     //    13. gx1 = gx1 * x1    
-    gx1 = gx1 + Fq::from_str("3").unwrap();            //    14. gx1 = gx1 + B
+    gx1 *= x1;                          //    13. gx1 = gx1 * x1    
+    gx1 += B;                           //    14. gx1 = gx1 + B
 
     // let gx1NotSquare: i32 = if gx1.legendre().is_qr() {0} else {-1};    //    15.  e1 = is_square(gx1)
     // gx1NotSquare = 0 if gx1 is a square, -1 otherwise
 
-    let x2: Fq = c2 + tv4;              //    16.  x2 = c2 + tv4
+    let x2: Fq = C2 + tv4;              //    16.  x2 = c2 + tv4
     let mut gx2: Fq = x2.square();      //    17. gx2 = x2²
     //    18. gx2 = gx2 + A     See line 12
-    gx2 = gx2 * x2;                     //    19. gx2 = gx2 * x2
-    gx2 = gx2 + Fq::from_str("3").unwrap();            //    20. gx2 = gx2 + B
+    gx2 *= x2;                          //    19. gx2 = gx2 * x2
+    gx2 += B;                           //    20. gx2 = gx2 + B
 
     let mut x3: Fq = tv2.square();      //    22.  x3 = tv2²
-    x3 = x3 * tv3;                      //    23.  x3 = x3 * tv3
+    x3 *= tv3;                          //    23.  x3 = x3 * tv3
     x3 = x3.square();                   //    24.  x3 = x3²
-    x3 = x3 * c4;                       //    25.  x3 = x3 * c4
-
-    x3 = x3 + z;                        //    26.  x3 = x3 + Z
-
-    let mut x: Fq = if gx1.legendre().is_qr() { x1 } else { x3};  //    27.   x = CMOV(x3, x1, e1)   # x = x1 if gx1 is square, else x = x3
-
-    if gx2.legendre().is_qr() && !gx1.legendre().is_qr() { x = x2 } //    28.   x = CMOV(x, x2, e2)    # x = x2 if gx2 is square and gx1 is not
-	// Select x2 iff gx2 is square and gx1 is not, iff gx1SquareOrGx2Not = 0
-    
-    let mut gx = x.square();    //    29.  gx = x²
-    //    30.  gx = gx + A
-    gx = gx * x;                //    31.  gx = gx * x
-    gx = gx + Fq::from_str("3").unwrap();      //    32.  gx = gx + B
-
-    let mut y: Fq = gx.sqrt().unwrap();     //    33.   y = sqrt(gx)
-
+    x3 *= C4;                           //    25.  x3 = x3 * c4
+
+    x3 += Z;                            //    26.  x3 = x3 + Z
+
+    // e1 = is_square(gx1), e2 = is_square(gx2), each square root is taken once and kept as y
+    let (x, mut y) = match Fq::sqrt_ratio(&gx1, &Fq::ONE) {
+        (true, y1) => (x1, y1),             //    27.   x = CMOV(x3, x1, e1)   # x = x1 if gx1 is square, else x = x3
+        (false, _) => match Fq::sqrt_ratio(&gx2, &Fq::ONE) {
+            (true, y2) => (x2, y2),         //    28.   x = CMOV(x, x2, e2)    # x = x2 if gx2 is square and gx1 is not
+            (false, _) => {
+                let mut gx = x3.square();   //    29.  gx = x²
+                //    30.  gx = gx + A
+                gx *= x3;                   //    31.  gx = gx * x
+                gx += B;                    //    32.  gx = gx + B
+                (x3, Fq::sqrt_ratio(&gx, &Fq::ONE).1)   //    33.   y = sqrt(gx)
+            }
+        },
+    };
 
     #[allow(non_snake_case)]
     let signsNotEqual = g1Sgn0(u) ^ g1Sgn0(y);
 
-    tv1 = Fq::from(0) - y;
-    //TODO: conditionallySelect
-    if signsNotEqual == 0 {y = y} else {y = tv1}
-    G1::new(x, y)
+    if signsNotEqual != 0 {
+        y = -y;
+    }
+    let res = G1::new_unchecked(x, y);
 
+    if !res.is_on_curve() {
+        panic!("Point not on curve")
+    }
 
-    
+    res.into()
+}
+
+// is_in_g1_subgroup checks that p is on the curve. G1 has cofactor 1, so every point of E(Fq) is
+// in the r-torsion and the [r]P = 0 check done by arkworks is not needed.
+pub fn is_in_g1_subgroup(p: &G1) -> bool {
+    p.is_on_curve()
 }
 
 #[allow(non_snake_case)]
 fn g1Sgn0(x: Fq) -> u64 {
-    let t: BigUint = x.into();
-    *BigUint::to_bytes_le(&t).get(0).unwrap() as u64 & 1
+    x.into_bigint().0[0] & 1
 }
 
 #[allow(non_snake_case)]
 pub fn g1NotZero(x: Fq) -> u64 {
 
-    let t: BigUint = x.into();
-    let t_vec = t.to_u64_digits();
-	return t_vec[0] | t_vec[1] | t_vec[2] | t_vec[3];
+    let t_vec = x.into_bigint().0;
+	t_vec[0] | t_vec[1] | t_vec[2] | t_vec[3]
 
 }
 
 #[allow(non_snake_case)]
 pub fn HashToG1(msg: &[u8], dst: &[u8]) -> G1 {
-    let u = Hash2FieldBN254::hash_to_field(msg, dst, 2);
-    let Q0 = MapToCurve1(u[0]);
-    let Q1 = MapToCurve1(u[1]);
-    let Q = Q0 + Q1;
-    Q.into()
+    HashToG1_projective(msg, dst).into_affine()
+}
+
+// HashToG1_projective is HashToG1 without the final normalization to affine coordinates
+#[allow(non_snake_case)]
+pub fn HashToG1_projective(msg: &[u8], dst: &[u8]) -> G1Projective {
+    let u = hash_to_field::<2>(msg, dst);
+    let Q0 = MapToCurve1_projective(u[0]);
+    let Q1 = MapToCurve1_projective(u[1]);
+    Q0 + Q1
+}
+
+// hash_to_g1_batch returns HashToG1(msg, dst) for every message. Montgomery's trick shares a single
+// field inversion across all the SVDW maps, and another one across the affine normalizations.
+pub fn hash_to_g1_batch(msgs: &[&[u8]], dst: &[u8]) -> Vec<G1> {
+    let u: Vec<Fq> = hash_to_field_batch::<2>(msgs, dst).into_iter().flatten().collect();
+    let q: Vec<G1Projective> = map_to_curve1_batch(&u)
+        .chunks_exact(2)
+        .map(|q| q[0] + q[1])
+        .collect();
+
+    G1Projective::normalize_batch(&q)
+}
+
+// map_to_curve1_batch returns MapToCurve1_projective(u) for every u with a single inversion.
+// batch_inversion leaves zeros in place, which is inv0.
+pub(crate) fn map_to_curve1_batch(u: &[Fq]) -> Vec<G1Projective> {
+    let tv: Vec<(Fq, Fq)> = u.iter().map(|u| svdw_tv(*u)).collect();
+    let mut tv3: Vec<Fq> = tv.iter().map(|(tv1, tv2)| *tv1 * tv2).collect();
+    batch_inversion(&mut tv3);
+
+    u.iter()
+        .zip(tv)
+        .zip(tv3)
+        .map(|((u, (tv1, tv2)), tv3)| svdw_finish(*u, tv1, tv2, tv3))
+        .collect()
+}
+
+// par_hash_to_g1 is hash_to_g1_batch spread over the rayon thread pool, the output keeps the order of msgs
+#[cfg(feature = "parallel")]
+pub fn par_hash_to_g1(msgs: &[&[u8]], dst: &[u8]) -> Vec<G1> {
+    msgs.par_chunks(PAR_CHUNK_SIZE)
+        .flat_map_iter(|chunk| hash_to_g1_batch(chunk, dst))
+        .collect()
+}
+
+// messages per rayon task, large enough for the shared inversions to pay off
+#[cfg(feature = "parallel")]
+pub(crate) const PAR_CHUNK_SIZE: usize = 64;
+
+// HashToG1With is HashToG1 with the given hash_to_field expander
+#[allow(non_snake_case)]
+pub fn HashToG1With(msg: &[u8], dst: &[u8], expander: Expander) -> G1 {
+    let u = Fq::hash_to_field_with(msg, dst, 2, expander);
+    let Q0 = MapToCurve1_projective(u[0]);
+    let Q1 = MapToCurve1_projective(u[1]);
+    (Q0 + Q1).into_affine()
+}
+
+#[cfg(test)]
+mod common_tests {
+
+    use crate::test_utils::hex_to_bytes;
+    use crate::hash2g1::{FromOkm, Fq};
+    use crate::hash2g1::{expand_message_into, hash_to_field, ExpandMsgSHA256, Hash2FieldBN254};
+    use crate::hash2g1::{HashToG1, HashToG1_projective, MapToCurve1, MapToCurve1_projective};
+    use crate::hash2g1::{hash_to_g1_batch, is_in_g1_subgroup, map_to_curve1_batch, G1};
+    use ark_bn254::Fr;
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::{Field, MontFp, UniformRand, Zero};
+    use ark_ff::PrimeField;
+    use ark_std::rand::RngCore;
+    use num_bigint::BigUint;
+
+    // reference reduction through BigUint
+    fn from_okm_biguint(data: &[u8; 48]) -> Fq {
+        let p: BigUint = Fq::MODULUS.into();
+        Fq::from(BigUint::from_bytes_be(&data[..]) % p)
+    }
+
+    #[test]
+    fn from_okm_test() {
+        let mut edge_cases = vec![[0u8; 48], [0xffu8; 48]];
+
+        // lo = p - 1, p, p + 1 and hi = 0
+        let p: BigUint = Fq::MODULUS.into();
+        for x in [&p - 1u32, p.clone(), &p + 1u32] {
+            let bytes = x.to_bytes_be();
+            let mut okm = [0u8; 48];
+            okm[48 - bytes.len()..].copy_from_slice(&bytes);
+            edge_cases.push(okm);
+        }
+
+        for okm in edge_cases.iter() {
+            assert_eq!(Fq::from_okm(okm), from_okm_biguint(okm));
+        }
+
+        let mut rng = ark_std::test_rng();
+        for _ in 0..10000 {
+            let mut okm = [0u8; 48];
+            rng.fill_bytes(&mut okm);
+            assert_eq!(Fq::from_okm(&okm), from_okm_biguint(&okm));
+        }
+    }
+
+    #[test]
+    fn expand_message_into_test() {
+        // https://www.rfc-editor.org/rfc/rfc9380.html#name-expand_message_xmdsha-256
+        let dst = b"QUUX-V01-CS02-with-expander-SHA256-128";
+        let mut out = [0u8; 0x20];
+        expand_message_into(b"", dst, &mut out);
+        assert_eq!(out[..], hex_to_bytes("68a985b87eb6b46952128911f2a4412bbc302a9d759667f87f7a21d803f07235"));
+        expand_message_into(b"abc", dst, &mut out);
+        assert_eq!(out[..], hex_to_bytes("d8ccab23b5985ccea865c6c97b6e5b8350e794e603b4b97902f53a8a0d605615"));
+
+        // same bytes as the allocating expander, including lengths that are not a multiple of 32
+        for len in [1, 31, 32, 33, 96, 128, 192, 255 * 32] {
+            let mut out = vec![0u8; len];
+            expand_message_into(b"abc", dst, &mut out);
+            assert_eq!(out, Fq::expand_message(b"abc", dst, len));
+        }
+    }
+
+    #[test]
+    fn hash2field_const_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+        for msg in [&b""[..], b"abc", b"abcdef0123456789"] {
+            assert_eq!(hash_to_field::<1>(msg, dst)[..], Fq::hash_to_field(msg, dst, 1)[..]);
+            assert_eq!(hash_to_field::<2>(msg, dst)[..], Fq::hash_to_field(msg, dst, 2)[..]);
+            assert_eq!(hash_to_field::<4>(msg, dst)[..], Fq::hash_to_field(msg, dst, 4)[..]);
+        }
+    }
+
+    #[test]
+    fn projective_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+        for msg in [&b""[..], b"abc", b"abcdef0123456789"] {
+            assert_eq!(HashToG1_projective(msg, dst).into_affine(), HashToG1(msg, dst));
+            let u = hash_to_field::<2>(msg, dst);
+            assert_eq!(MapToCurve1_projective(u[0]).into_affine(), MapToCurve1(u[0]));
+        }
+    }
+
+    #[test]
+    fn hash_to_g1_batch_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+        let msgs: Vec<Vec<u8>> = (0..50u32).map(|i| i.to_be_bytes().repeat(i as usize)).collect();
+        let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+
+        let batch = hash_to_g1_batch(&msgs, dst);
+        assert_eq!(batch.len(), msgs.len());
+        for (msg, q) in msgs.iter().zip(batch) {
+            assert_eq!(q, HashToG1(msg, dst));
+        }
+        assert!(hash_to_g1_batch(&[], dst).is_empty());
+    }
+
+    #[test]
+    fn exceptional_u_test() {
+        // tv1 = 1 - 4u² vanishes at u = ±1/2, where inv0(tv1 * tv2) = 0 and x = c2
+        let half = Fq::from(2u64).inverse().unwrap();
+        let u = [half, -half, Fq::ZERO];
+        for (u, q) in u.iter().zip(map_to_curve1_batch(&u)) {
+            assert_eq!(q, MapToCurve1_projective(*u));
+        }
+
+        // computed independently from the straight-line SVDW of the hash-to-curve draft
+        let q = G1::new(
+            MontFp!("10944121435919637611123202872628637544348155578648911831344518947322613104291"),
+            MontFp!("4718603453640367770405249522358112449463417117041194427604452040985121683380"),
+        );
+        assert_eq!(MapToCurve1(half), q);
+        assert_eq!(MapToCurve1(-half), -q);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn par_hash_to_g1_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+        for n in [0u32, 1, 63, 64, 65, 300] {
+            let msgs: Vec<Vec<u8>> = (0..n).map(|i| i.to_le_bytes().to_vec()).collect();
+            let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+            let sequential: Vec<G1> = msgs.iter().map(|msg| HashToG1(msg, dst)).collect();
+            assert_eq!(crate::hash2g1::par_hash_to_g1(&msgs, dst), sequential);
+        }
+    }
+
+    #[test]
+    fn is_in_g1_subgroup_test() {
+        let mut rng = ark_std::test_rng();
+        for _ in 0..10 {
+            let p = MapToCurve1(Fq::rand(&mut rng));
+            assert!(is_in_g1_subgroup(&p));
+            assert!(p.mul_bigint(Fr::MODULUS).is_zero());
+
+            let mut off = p;
+            off.y += Fq::ONE;
+            assert!(!is_in_g1_subgroup(&off));
+        }
+        assert!(is_in_g1_subgroup(&G1::identity()));
+        assert!(is_in_g1_subgroup(&G1::generator()));
+    }
+
+    #[test]
+    fn from_okm_fr_test() {
+        let r: BigUint = Fr::MODULUS.into();
+        let mut rng = ark_std::test_rng();
+        for _ in 0..10000 {
+            let mut okm = [0u8; 48];
+            rng.fill_bytes(&mut okm);
+            assert_eq!(Fr::from_okm(&okm), Fr::from(BigUint::from_bytes_be(&okm) % &r));
+        }
+
+        let dst = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+        let mut okm = [0u8; 96];
+        expand_message_into(b"abc", dst, &mut okm);
+        let u = Fr::hash_to_field(b"abc", dst, 2);
+        assert_eq!(u[0], Fr::from(BigUint::from_bytes_be(&okm[..48]) % &r));
+        assert_eq!(u[1], Fr::from(BigUint::from_bytes_be(&okm[48..]) % &r));
+    }
 }
 
-#[cfg(all(feature = "gnark_crypto_compatible"))]
+#[cfg(feature = "gnark_crypto_compatible")]
 #[cfg(test)]
 mod tests {
 
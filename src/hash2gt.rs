@@ -0,0 +1,59 @@
+use ark_bn254::{Bn254, G1Affine, G2Affine};
+use ark_ec::{pairing::{Pairing, PairingOutput}, AffineRepr};
+use crate::hash2g1::HashToG1;
+use crate::hash2g2::HashToG2;
+
+// Hashing into GT, the order r subgroup of Fq12*, by pairing a hash to G1 with the generator of G2
+// (or the generator of G1 with a hash to G2). e(., g2) is an isomorphism G1 -> GT, so a random oracle
+// into G1 gives a random oracle into GT and the output is canonical after the final exponentiation.
+//
+// Caveats:
+// - the discrete log of the output to the base e(g1, g2) is the discrete log of HashToG1(msg) to the
+//   base g1, the output is exactly as opaque as the G1 point and no more
+// - the G1 and G2 constructions are different functions, a protocol must fix one of them per dst
+// - the output is in GT only, not a uniform element of Fq12, and costs one pairing per message
+// - hash_to_gt_g2 inherits the dependency of HashToG2 on the SVDW flavour of the enabled feature
+
+// hash_to_gt returns e(HashToG1(msg, dst), g2)
+pub fn hash_to_gt(msg: &[u8], dst: &[u8]) -> PairingOutput<Bn254> {
+    Bn254::pairing(HashToG1(msg, dst), G2Affine::generator())
+}
+
+// hash_to_gt_g2 returns e(g1, HashToG2(msg, dst))
+pub fn hash_to_gt_g2(msg: &[u8], dst: &[u8]) -> PairingOutput<Bn254> {
+    Bn254::pairing(G1Affine::generator(), HashToG2(msg, dst))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{hash_to_gt, hash_to_gt_g2};
+    use crate::hash2g1::HashToG1;
+    use ark_bn254::{Bn254, Fq12, Fr, G2Affine};
+    use ark_ec::{pairing::Pairing, AffineRepr, CurveGroup};
+    use ark_ff::{Field, PrimeField, UniformRand, Zero};
+
+    const DST: &[u8] = b"QUUX-V01-CS02-with-BN254GT_XMD:SHA-256_SVDW_RO_";
+
+    #[test]
+    fn hash_to_gt_test() {
+        let mut rng = ark_std::test_rng();
+        for msg in [&b""[..], b"abc", b"abcdef0123456789", &[0x61; 200]] {
+            let h = hash_to_gt(msg, DST);
+            assert_eq!(h, hash_to_gt(msg, DST));
+            assert!(!h.is_zero());
+            assert_ne!(h, hash_to_gt(msg, b"another dst"));
+            assert_ne!(h, hash_to_gt_g2(msg, DST));
+
+            // the output has order r
+            assert_eq!(h.0.pow(Fr::MODULUS), Fq12::ONE);
+            assert_eq!(hash_to_gt_g2(msg, DST).0.pow(Fr::MODULUS), Fq12::ONE);
+
+            // bilinearity carries over from G1
+            let k = Fr::rand(&mut rng);
+            let hk = Bn254::pairing((HashToG1(msg, DST) * k).into_affine(), G2Affine::generator());
+            assert_eq!(h * k, hk);
+        }
+        assert_ne!(hash_to_gt(b"abc", DST), hash_to_gt(b"abd", DST));
+    }
+}
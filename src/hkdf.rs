@@ -0,0 +1,87 @@
+use sha2::{Digest, Sha256};
+
+// HMAC-SHA256 (RFC 2104) and HKDF-SHA256 (RFC 5869), just enough for the KeyGen of the BLS draft.
+// data is hashed as the concatenation of its slices.
+
+const BLOCK_SIZE: usize = 64;
+
+pub(crate) fn hmac_sha256(key: &[u8], data: &[&[u8]]) -> [u8; 32] {
+    let mut k = [0u8; BLOCK_SIZE];
+    if key.len() > BLOCK_SIZE {
+        k[..32].copy_from_slice(&Sha256::digest(key));
+    } else {
+        k[..key.len()].copy_from_slice(key);
+    }
+
+    let mut inner = Sha256::new();
+    inner.update(k.map(|b| b ^ 0x36));
+    for d in data {
+        inner.update(d);
+    }
+
+    let mut outer = Sha256::new();
+    outer.update(k.map(|b| b ^ 0x5c));
+    outer.update(inner.finalize());
+    outer.finalize().into()
+}
+
+// PRK = HMAC-Hash(salt, IKM)
+pub(crate) fn hkdf_extract(salt: &[u8], ikm: &[&[u8]]) -> [u8; 32] {
+    hmac_sha256(salt, ikm)
+}
+
+// T(i) = HMAC-Hash(PRK, T(i - 1) | info | i), OKM = first out.len() bytes of T(1) | T(2) | ...
+pub(crate) fn hkdf_expand(prk: &[u8; 32], info: &[&[u8]], out: &mut [u8]) {
+    if out.len() > 255 * 32 {
+        panic!("hkdf output is too long");
+    }
+
+    let mut t = [0u8; 32];
+    for (i, chunk) in out.chunks_mut(32).enumerate() {
+        let counter = [i as u8 + 1];
+        let prev: &[u8] = if i == 0 { &[] } else { &t };
+        let mut data = alloc::vec![prev];
+        data.extend_from_slice(info);
+        data.push(&counter);
+        t = hmac_sha256(prk, &data);
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::hex_to_bytes;
+    use super::{hkdf_expand, hkdf_extract, hmac_sha256};
+
+    // https://www.rfc-editor.org/rfc/rfc4231#section-4.2 and #section-4.7
+    #[test]
+    fn hmac_test() {
+        let mac = hmac_sha256(&[0x0b; 20], &[b"Hi There"]);
+        assert_eq!(mac.to_vec(), hex_to_bytes("b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"));
+
+        let mac = hmac_sha256(&[0xaa; 131], &[b"This is a test using a larger than block-size key and a larger than block-size data. The key needs to be hashed before being used by the HMAC algorithm."]);
+        assert_eq!(mac.to_vec(), hex_to_bytes("9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2"));
+    }
+
+    // https://www.rfc-editor.org/rfc/rfc5869#appendix-A.1 and A.3
+    #[test]
+    fn hkdf_test() {
+        let ikm = [0x0b; 22];
+        let salt = hex_to_bytes("000102030405060708090a0b0c");
+        let info = hex_to_bytes("f0f1f2f3f4f5f6f7f8f9");
+
+        let prk = hkdf_extract(&salt, &[&ikm]);
+        assert_eq!(prk.to_vec(), hex_to_bytes("077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5"));
+
+        // info split across slices is the same as one slice
+        let mut okm = [0u8; 42];
+        hkdf_expand(&prk, &[&info[..3], &info[3..]], &mut okm);
+        assert_eq!(okm.to_vec(), hex_to_bytes("3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf34007208d5b887185865"));
+
+        let prk = hkdf_extract(b"", &[&ikm]);
+        let mut okm = [0u8; 42];
+        hkdf_expand(&prk, &[], &mut okm);
+        assert_eq!(okm.to_vec(), hex_to_bytes("8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d9d201395faa4b61a96c8"));
+    }
+}
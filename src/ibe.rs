@@ -0,0 +1,206 @@
+use ark_bn254::{Bn254, Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{pairing::{Pairing, PairingOutput}, AffineRepr, CurveGroup, Group};
+use ark_ff::{UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use alloc::vec::Vec;
+use crate::bls::min_pk;
+use crate::glv::mul_glv_g1_ct;
+use crate::hash2g1::{expand_message_into, is_in_g1_subgroup, Hash2FieldBN254};
+use crate::hash2g2::{is_in_g2_subgroup, HashToG2};
+
+// Boneh-Franklin identity based encryption on BN254, with the master public key in G1 and identities
+// hashed to G2. The key of an identity is [s] HashToG2(id), i.e. a bls::min_pk signature on id under
+// IDENTITY_DST, so a beacon that signs round numbers hands out the keys of past rounds (timelock).
+// H2 and H4 are expand_message_xmd, H3 is hash_to_field over Fr.
+// https://crypto.stanford.edu/~dabo/papers/bfibe.pdf
+
+pub const IDENTITY_DST: &[u8] = b"IBE_BN254G2_XMD:SHA-256_SVDW_RO_H1_";
+const H2_DST: &[u8] = b"IBE_BN254G2_XMD:SHA-256_SVDW_RO_H2_";
+const H3_DST: &[u8] = b"IBE_BN254G2_XMD:SHA-256_SVDW_RO_H3_";
+const H4_DST: &[u8] = b"IBE_BN254G2_XMD:SHA-256_SVDW_RO_H4_";
+
+// length of sigma in FullIdent
+const SIGMA_LEN: usize = 32;
+
+pub struct BasicCiphertext {
+    pub u: G1Affine,
+    pub v: Vec<u8>,
+}
+
+pub struct Ciphertext {
+    pub u: G1Affine,
+    pub v: [u8; SIGMA_LEN],
+    pub w: Vec<u8>,
+}
+
+// setup returns the master secret s and the master public key [s] g1
+pub fn setup<R: Rng + ?Sized>(rng: &mut R) -> (Fr, G1Affine) {
+    let mut s = Fr::rand(rng);
+    while s.is_zero() {
+        s = Fr::rand(rng);
+    }
+    (s, min_pk::sk_to_pk(&s))
+}
+
+// extract returns the private key [s] HashToG2(id) of an identity
+pub fn extract(msk: &Fr, id: &[u8]) -> G2Affine {
+    min_pk::core_sign(msk, id, IDENTITY_DST)
+}
+
+// verify_key checks e(mpk, HashToG2(id)) == e(g1, sk_id)
+pub fn verify_key(mpk: &G1Affine, id: &[u8], sk_id: &G2Affine) -> bool {
+    min_pk::core_verify(mpk, id, sk_id, IDENTITY_DST)
+}
+
+// H2, a mask of out.len() bytes from an element of GT
+fn mask_from_gt(g: &PairingOutput<Bn254>, out: &mut [u8]) {
+    let mut bytes = Vec::new();
+    g.serialize_compressed(&mut bytes).unwrap();
+    expand_message_into(&bytes, H2_DST, out);
+}
+
+fn xor(a: &mut [u8], b: &[u8]) {
+    for (a, b) in a.iter_mut().zip(b) {
+        *a ^= b;
+    }
+}
+
+// U = [r] g1 and g_id^r = e([r] mpk, HashToG2(id)), r applied in G1 rather than in GT
+fn encapsulate(mpk: &G1Affine, id: &[u8], r: &Fr) -> (G1Affine, PairingOutput<Bn254>) {
+    let u = mul_glv_g1_ct(&G1Projective::generator(), r).into_affine();
+    let g = Bn254::pairing(mul_glv_g1_ct(&mpk.into_group(), r), HashToG2(id, IDENTITY_DST));
+    (u, g)
+}
+
+// encrypt_basic is BasicIdent: U = [r] g1, V = msg xor H2(g_id^r). It is only CPA secure.
+pub fn encrypt_basic<R: Rng + ?Sized>(mpk: &G1Affine, id: &[u8], msg: &[u8], rng: &mut R) -> BasicCiphertext {
+    let (u, g) = encapsulate(mpk, id, &Fr::rand(rng));
+    let mut v = msg.to_vec();
+    let mut mask = alloc::vec![0u8; msg.len()];
+    mask_from_gt(&g, &mut mask);
+    xor(&mut v, &mask);
+    BasicCiphertext { u, v }
+}
+
+// decrypt_basic returns V xor H2(e(U, sk_id)), None if U or the key is not a valid point
+pub fn decrypt_basic(sk_id: &G2Affine, ct: &BasicCiphertext) -> Option<Vec<u8>> {
+    if !is_in_g1_subgroup(&ct.u) || !is_in_g2_subgroup(sk_id) {
+        return None;
+    }
+    let g = Bn254::pairing(ct.u, sk_id);
+    let mut msg = ct.v.clone();
+    let mut mask = alloc::vec![0u8; msg.len()];
+    mask_from_gt(&g, &mut mask);
+    xor(&mut msg, &mask);
+    Some(msg)
+}
+
+// H3(sigma, msg)
+fn fo_scalar(sigma: &[u8; SIGMA_LEN], msg: &[u8]) -> Fr {
+    Fr::hash_to_field(&[&sigma[..], msg].concat(), H3_DST, 1)[0]
+}
+
+// encrypt is FullIdent, BasicIdent under the Fujisaki-Okamoto transform: r = H3(sigma, msg),
+// U = [r] g1, V = sigma xor H2(g_id^r), W = msg xor H4(sigma)
+pub fn encrypt<R: Rng + ?Sized>(mpk: &G1Affine, id: &[u8], msg: &[u8], rng: &mut R) -> Ciphertext {
+    let mut sigma = [0u8; SIGMA_LEN];
+    rng.fill_bytes(&mut sigma);
+    let (u, g) = encapsulate(mpk, id, &fo_scalar(&sigma, msg));
+
+    let mut v = sigma;
+    let mut mask = [0u8; SIGMA_LEN];
+    mask_from_gt(&g, &mut mask);
+    xor(&mut v, &mask);
+
+    let mut w = msg.to_vec();
+    let mut mask = alloc::vec![0u8; msg.len()];
+    expand_message_into(&sigma, H4_DST, &mut mask);
+    xor(&mut w, &mask);
+
+    Ciphertext { u, v, w }
+}
+
+// decrypt recovers sigma and msg and checks U = [H3(sigma, msg)] g1, None on any failure, in
+// particular under the key of another identity
+pub fn decrypt(sk_id: &G2Affine, ct: &Ciphertext) -> Option<Vec<u8>> {
+    if ct.u.is_zero() || !is_in_g1_subgroup(&ct.u) || !is_in_g2_subgroup(sk_id) {
+        return None;
+    }
+    let g = Bn254::pairing(ct.u, sk_id);
+    let mut sigma = ct.v;
+    let mut mask = [0u8; SIGMA_LEN];
+    mask_from_gt(&g, &mut mask);
+    xor(&mut sigma, &mask);
+
+    let mut msg = ct.w.clone();
+    let mut mask = alloc::vec![0u8; msg.len()];
+    expand_message_into(&sigma, H4_DST, &mut mask);
+    xor(&mut msg, &mask);
+
+    let r = fo_scalar(&sigma, &msg);
+    if mul_glv_g1_ct(&G1Projective::generator(), &r).into_affine() != ct.u {
+        return None;
+    }
+    Some(msg)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{decrypt, decrypt_basic, encrypt, encrypt_basic, extract, setup, verify_key};
+    use ark_bn254::{G1Affine, G2Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+
+    #[test]
+    fn basic_ident_test() {
+        let mut rng = ark_std::test_rng();
+        let (msk, mpk) = setup(&mut rng);
+        let sk_id = extract(&msk, b"round 1000");
+        assert!(verify_key(&mpk, b"round 1000", &sk_id));
+        assert!(!verify_key(&mpk, b"round 1001", &sk_id));
+
+        for msg in [&b""[..], b"hello", &[0x42; 300]] {
+            let ct = encrypt_basic(&mpk, b"round 1000", msg, &mut rng);
+            assert_eq!(decrypt_basic(&sk_id, &ct).unwrap(), msg);
+            if !msg.is_empty() {
+                assert_ne!(ct.v, msg);
+                assert_ne!(decrypt_basic(&extract(&msk, b"round 1001"), &ct).unwrap(), msg);
+            }
+        }
+        let ct = encrypt_basic(&mpk, b"round 1000", b"hello", &mut rng);
+        assert!(decrypt_basic(&G2Affine::new_unchecked(sk_id.x, sk_id.x), &ct).is_none());
+    }
+
+    #[test]
+    fn full_ident_test() {
+        let mut rng = ark_std::test_rng();
+        let (msk, mpk) = setup(&mut rng);
+        let sk_id = extract(&msk, b"round 1000");
+
+        for msg in [&b""[..], b"hello", &[0x42; 300]] {
+            let ct = encrypt(&mpk, b"round 1000", msg, &mut rng);
+            assert_eq!(decrypt(&sk_id, &ct).unwrap(), msg);
+
+            // the key of another identity or of another master key is rejected
+            assert!(decrypt(&extract(&msk, b"round 1001"), &ct).is_none());
+            let (other_msk, _) = setup(&mut rng);
+            assert!(decrypt(&extract(&other_msk, b"round 1000"), &ct).is_none());
+        }
+
+        // any change to the ciphertext is detected
+        let ct = encrypt(&mpk, b"round 1000", b"hello", &mut rng);
+        let mut bad = encrypt(&mpk, b"round 1000", b"hello", &mut rng);
+        bad.w[0] ^= 1;
+        assert!(decrypt(&sk_id, &bad).is_none());
+        let mut bad = encrypt(&mpk, b"round 1000", b"hello", &mut rng);
+        bad.v[0] ^= 1;
+        assert!(decrypt(&sk_id, &bad).is_none());
+        let mut bad = encrypt(&mpk, b"round 1000", b"hello", &mut rng);
+        bad.u = (bad.u + G1Affine::generator()).into_affine();
+        assert!(decrypt(&sk_id, &bad).is_none());
+        bad.u = G1Affine::zero();
+        assert!(decrypt(&sk_id, &bad).is_none());
+        assert_eq!(decrypt(&sk_id, &ct).unwrap(), b"hello");
+    }
+}
@@ -1,2 +1,28 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+// the G2 map constants depend on the implementation the outputs must match
+#[cfg(not(any(feature = "constantine_compatible", feature = "gnark_crypto_compatible")))]
+compile_error!("enable either the `constantine_compatible` or the `gnark_crypto_compatible` feature");
+
+pub mod blind;
+pub mod bls;
+pub mod cache;
+pub mod endomorphism;
+pub mod generators;
+pub mod glv;
 pub mod  hash2g1;
-pub mod hash2g2;
\ No newline at end of file
+pub mod hash2g2;
+pub mod hash2gt;
+mod hkdf;
+pub mod ibe;
+pub mod mimc;
+pub mod multibuf;
+pub mod oprf;
+pub mod sqrt;
+#[cfg(test)]
+mod test_utils;
+pub mod threshold;
+pub mod transcript;
+pub mod vrf;
@@ -0,0 +1,485 @@
+use ark_bn254::fr::Fr;
+use ark_ff::{BigInteger256, Field, MontFp, PrimeField};
+use alloc::vec::Vec;
+
+// MiMC over the BN254 scalar field with the parameters of gnark-crypto's `ecc/bn254/fr/mimc`
+// (x^5 permutation, 110 rounds, round constants derived from keccak256("seed")), absorbed with the
+// Miyaguchi-Preneel construction h = E_h(m) + h + m.
+// https://github.com/Consensys/gnark-crypto/blob/master/ecc/bn254/fr/mimc/mimc.go
+
+pub const MIMC_NB_ROUNDS: usize = 110;
+pub const BLOCK_SIZE: usize = 32;
+
+// c_0 = keccak256(keccak256("seed")), c_(i+1) = keccak256(c_i), each reduced mod r
+const MIMC_CONSTANTS: [Fr; MIMC_NB_ROUNDS] = [
+    MontFp!("227063593160049201514509818732644766896230235191445544141110657236065169432"),
+    MontFp!("14216930871394413475885543358391969001796912808625170576412941718425727480905"),
+    MontFp!("13091462576550089354261023627641753004926491134347784566278243144585841078417"),
+    MontFp!("18736023174290548165050765799231505541711012637972192037099796877637059010016"),
+    MontFp!("796636033841689627732941016044857384234234277501564259311815186813195010627"),
+    MontFp!("7049792165217502363114227773374115492495393176744730189515562778035071867821"),
+    MontFp!("17004095116726405864684454804540866859059278240914071423178037737714962317801"),
+    MontFp!("14110268636549425055632566045581853560423521131037962488540655987535191004969"),
+    MontFp!("18183635788335456259215276538456634373878691301055828686319747253615002143747"),
+    MontFp!("17094270359512653934788537386985943119745071422450083986863088746253169651698"),
+    MontFp!("21606397331421151312290269496743528579353487580150269962583704985025203683566"),
+    MontFp!("11482796835106945909650417409009375869128464918808201005317159508926845333372"),
+    MontFp!("5896114894234359837481980051604224653571872854250471410846947653395077045175"),
+    MontFp!("8043758726292679243102809161324039047742869268808278302475346342056293903111"),
+    MontFp!("9765227797118338345724719313674898871992672983681676861011354974715998221736"),
+    MontFp!("9980184672909482180637695009382192818723793158333771031442726952979088300949"),
+    MontFp!("8231877132811199596376758288825197494440517476607659739835166243301765860904"),
+    MontFp!("8335067676479817842493472758560802142744298375820509901958843910507461215099"),
+    MontFp!("10841545820231554131682518174137979197520487236302295350589030622478073612580"),
+    MontFp!("3219131731887960949807515150723614694444414566887389129377006765182004280513"),
+    MontFp!("56804755552986645089184612629551548380712103263713508879501466305875964502"),
+    MontFp!("3063594241115875600174308534745809602942823704041628148569154884406804087107"),
+    MontFp!("1022229143886614551843240999132524298883977051285206014564945818204512723699"),
+    MontFp!("1247948173836835613759834564361354902760693928209107555848903547602125609667"),
+    MontFp!("12690047342343207986715505449836807591806230840704578918412884362668236488424"),
+    MontFp!("9456585747207468967136341612034989517427340607940281880317747335469436896657"),
+    MontFp!("10555679902623742965715379393380415053883065457992409910544092743581080934995"),
+    MontFp!("7642145723831431937150654031296178463709608595366450210492201904757626429246"),
+    MontFp!("12796285368351778411157416332578703705714646412236885840835353324717839499288"),
+    MontFp!("9920917725324856014628946457815467011979864273734012436016568174149575073620"),
+    MontFp!("1806771888767844400796964154165462987833794566790129616905621802681918305653"),
+    MontFp!("2237188035570518200375801347148339263941951653352635838130411033524031543911"),
+    MontFp!("6159774869789305950383877854134202099758528146886459191738581516739660641536"),
+    MontFp!("2159153222189174173490067225063044363535871059524538695070191871847470955412"),
+    MontFp!("3796681237523026223086145426486778389352604372052172299127843115700063953978"),
+    MontFp!("15056204194454071177732947070380798505823141690312550077512103668193190650776"),
+    MontFp!("18847697144542616776597460523489465741015527416695791143858315271487053716345"),
+    MontFp!("6010749183509972177829296064870149897270623093292652040160770247410917400713"),
+    MontFp!("18573886017870388584791853665036341308998474745558018999552747786306327187163"),
+    MontFp!("1902990407634160450975366476679732066298558065179856843056247078583090353402"),
+    MontFp!("5056480146405086811789505170440731715530475328844870175949109998024731067467"),
+    MontFp!("15740426253908866033612398810786354575055336092664709132388682334602601168702"),
+    MontFp!("491250169370634115048394492066021687801835886554368663023106896215909698645"),
+    MontFp!("5255739895973293668031562539559209975940249484631633008164126407281628232615"),
+    MontFp!("2993874367492450065981125977298561936411381709727853908030896236122420343727"),
+    MontFp!("1403914884782249096009089982237816316006749353131179527973160317711491651076"),
+    MontFp!("12914056360493359423764695636160432190520475662743083737270395470517659710829"),
+    MontFp!("2917404364788167044194419588360849732661365640462661072201491302974369825438"),
+    MontFp!("20784103425950430825528915699354924111453274156179753313577396452562475630409"),
+    MontFp!("1316449090346410801845183915381769525990226349513436734911941391785200212382"),
+    MontFp!("19891032074353122751368091896719823139652894181016649395806048173493086857338"),
+    MontFp!("5815046378509054585353936553633012260823210849110325320012946858007466529124"),
+    MontFp!("9342667946085721753232292005472701104293420214150876291070202875265183228493"),
+    MontFp!("3220266212393036831161802760991433604684006326671889836355824255100900631167"),
+    MontFp!("5129486740981610555565012597292200072154542792843090445908325336406070684212"),
+    MontFp!("10365499242482502687915472615946022335465942941657641380012062207514707672369"),
+    MontFp!("4611075984531475563366272046528439696064144614475739366357201914182455577262"),
+    MontFp!("12274444357037046733725220420843071726458636107722716111189924462679653993388"),
+    MontFp!("2444021750719441015829197081940411467903641739650651394173044346750720208186"),
+    MontFp!("15143675381185307178500906868356334825651015737618718091251777377451213407009"),
+    MontFp!("12298344485990016534010212669317442637641970988734864662627733004522811247925"),
+    MontFp!("407792086961455574135957029358146763364316705425829200860200716711144772110"),
+    MontFp!("14686495456688325356229693863075020970632170023662416843806799342111029622608"),
+    MontFp!("18951855733129374999539824238637835284715674696067944400774670287760774220945"),
+    MontFp!("16334111234389595299193801902740634241244222168925513590632042896157659801559"),
+    MontFp!("19623255796206582213343044956093476486139104852525580813879681620362890897558"),
+    MontFp!("19284965820494284222482683988641716023855422844851137438394494268143521834633"),
+    MontFp!("5042179171081431331282902567660865915154957134450098475064856996863766266700"),
+    MontFp!("19075637350940522721481728672122652040982051980503549922053017343878171287859"),
+    MontFp!("3096096603894689121667217859533027222641140852874591863405531829483163197840"),
+    MontFp!("21543191916254714877479305695881635899536323218308582727971577317237448630394"),
+    MontFp!("9980826669647369562409093155367822719846527509077693563581519695180055111612"),
+    MontFp!("15696810051723434179520892802382061883123916463500679794859575791011338569408"),
+    MontFp!("16710441142546269914456840870536846684666759198340322352862050391462853257859"),
+    MontFp!("16784162115836373795735205525741716397089015491804805056190418507628689514930"),
+    MontFp!("20919057090859990208154240431041177593233739098537292808899071595933232729923"),
+    MontFp!("10599687814613664602758829894851759731719366381965307423459731431292674962169"),
+    MontFp!("18092495413286015678790630168208787644418599959399842781132549515553139410584"),
+    MontFp!("3711799916574241475420555831932793749725513171598155737019147179555971323932"),
+    MontFp!("3878599345777774665565912098811702945088203032347412020650440180042070635932"),
+    MontFp!("5221687210067764220342563941232799146265831780579450576980295260767640382879"),
+    MontFp!("8572221995878907446339305767802962859956678949340179087676700081887070991418"),
+    MontFp!("13250870432967790116799427082816480335296645135069568814513747123924233796635"),
+    MontFp!("1762401353042500109291165674468304204146747021756564981770933537807125319114"),
+    MontFp!("16297297017503580916701479288278297532093130260977290972316747472919454831982"),
+    MontFp!("11301542023144145761538286188600886091507808962937720724476656305360091843144"),
+    MontFp!("3226463335346792970204307734198400221579260082314988957001789813920653640539"),
+    MontFp!("18201479370055215790852976435001157175848060363403485699590922691559044268816"),
+    MontFp!("1421776804632889503250299670147988126727383540687678953592715279854500795359"),
+    MontFp!("21230806036983379610681285136437154793727917065272091459526637553303154098111"),
+    MontFp!("4890882571712671501605561097268997756779482040164834629188098947876004725416"),
+    MontFp!("5593942559448006934122110327465529553527338130800655100545929619006646130703"),
+    MontFp!("19858351320072490775901034833039724699209320536870921374639489244857675659132"),
+    MontFp!("20569043303914081560731019065398457647606565616902130240528129599578592228968"),
+    MontFp!("10079763651682455157739628234628529503046958675131789218119668348482240995889"),
+    MontFp!("12075806963751214072241023676113780594016698427239126221026218940878020594099"),
+    MontFp!("16943711675576883986628449992969978423674439022821403957709854115749711096791"),
+    MontFp!("1649367951959654604433060041378790418650827672660780721804854858634469108499"),
+    MontFp!("11957779911765486656644689149330846943313705416524223567398485006010159944456"),
+    MontFp!("1467372234246581691639910443837800274464279239719080130524501855420568931562"),
+    MontFp!("16345733847331835103389317805143010119891715846287496394786195665951149072330"),
+    MontFp!("7448836565550394578623806516077867680872791214995424737491744252881969933895"),
+    MontFp!("8650625054615070484889009442902102532553165757475036656003778307974620126687"),
+    MontFp!("11907653828035696663714143522983869211190719525809271814618637057421334515531"),
+    MontFp!("2886235945117591824771809965323805334808414280306969797067740169710933875743"),
+    MontFp!("10917882835509774955453905588848475782845168316402655627358883243042341451042"),
+    MontFp!("19971838851328344406118398405782812664383760583892867152477371808954818808044"),
+    MontFp!("18265625854115489546229892300234363068277159796553916584413873595353870332297"),
+    MontFp!("11541833244575501930159939361686046962070402099593978040285396521535417462043"),
+    MontFp!("14681674628590376571212438852682626513594958603045820146231225156751765152354"),
+];
+
+// Errors of MiMC::write, the same two cases gnark's Write reports
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+    // the input is longer than one block and its length is not a multiple of BLOCK_SIZE
+    InvalidLength,
+    // a block is not the big-endian encoding of an element of Fr
+    NonCanonical,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MiMC {
+    h: Fr,
+}
+
+impl MiMC {
+    pub fn new() -> Self {
+        MiMC { h: Fr::ZERO }
+    }
+
+    // Write absorbs data as a sequence of 32 byte big-endian field elements, same as mimc.NewMiMC().Write:
+    // inputs shorter than one block are left-padded, every block must be a canonical element of Fr.
+    // Unlike gnark, nothing is absorbed when an error is returned.
+    pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+        if data.is_empty() {
+            return Ok(());
+        }
+
+        if data.len() < BLOCK_SIZE {
+            let mut block = [0u8; BLOCK_SIZE];
+            block[BLOCK_SIZE - data.len()..].copy_from_slice(data);
+            self.write_element(fr_from_be_bytes(&block).ok_or(Error::NonCanonical)?);
+            return Ok(());
+        }
+
+        let blocks = data.chunks_exact(BLOCK_SIZE);
+        if !blocks.remainder().is_empty() {
+            return Err(Error::InvalidLength);
+        }
+
+        let elements = blocks
+            .map(|block| fr_from_be_bytes(block.try_into().unwrap()))
+            .collect::<Option<Vec<Fr>>>()
+            .ok_or(Error::NonCanonical)?;
+        for m in elements {
+            self.write_element(m);
+        }
+        Ok(())
+    }
+
+    pub fn write_element(&mut self, m: Fr) {
+        let r = self.encrypt(m);
+        self.h = r + self.h + m;
+    }
+
+    // Sum returns the current digest without resetting the state, like gnark's Sum(nil)
+    pub fn sum(&self) -> Fr {
+        self.h
+    }
+
+    pub fn reset(&mut self) {
+        self.h = Fr::ZERO;
+    }
+
+    fn encrypt(&self, mut m: Fr) -> Fr {
+        for c in MIMC_CONSTANTS.iter() {
+            // m = (m+k+c)^5
+            let tmp = m + self.h + c;
+            m = tmp.square().square() * tmp;
+        }
+        m + self.h
+    }
+}
+
+fn fr_from_be_bytes(block: &[u8; BLOCK_SIZE]) -> Option<Fr> {
+    let mut limbs = [0u64; 4];
+    for (i, limb) in limbs.iter_mut().enumerate() {
+        let start = BLOCK_SIZE - 8 * (i + 1);
+        *limb = u64::from_be_bytes(block[start..start + 8].try_into().unwrap());
+    }
+    Fr::from_bigint(BigInteger256::new(limbs))
+}
+
+// hash_to_field_mimc returns count digests u_i = MiMC(len(dst) || dst || len(msg) || msg || i), the
+// Expander::MiMC option of Hash2FieldBN254 and HashToG1With.
+// gnark-crypto only defines hash_to_field with expand_message_xmd, so this framing is specific to this
+// crate. It only uses mimc.Write on whole blocks, a gnark circuit or Go verifier recomputes each u_i
+// with a fresh hasher:
+//   h := mimc.NewMiMC()
+//   h.Write(be32(len(dst))), then h.Write(c) for every 31 byte chunk c of dst
+//   h.Write(be32(len(msg))), then h.Write(c) for every 31 byte chunk c of msg
+//   h.Write(be32(i)), u_i = h.Sum(nil)
+// where be32 is the 32 byte big-endian encoding and each chunk is left-padded to a block by Write.
+// Sum(nil) keeps the running state, so reusing h for u_{i+1} would hash prefix || be32(i) || be32(i+1):
+// every u_i needs its own mimc.NewMiMC() that rewrites the whole prefix before be32(i).
+pub fn hash_to_field_mimc(msg: &[u8], dst: &[u8], count: usize) -> Vec<Fr> {
+
+    // a chunk of at most 31 bytes is always below r, the left-padded block is canonical
+    let mut prefix = MiMC::new();
+    prefix.write_element(Fr::from(dst.len() as u64));
+    for chunk in dst.chunks(BLOCK_SIZE - 1) {
+        prefix.write_element(Fr::from_be_bytes_mod_order(chunk));
+    }
+    prefix.write_element(Fr::from(msg.len() as u64));
+    for chunk in msg.chunks(BLOCK_SIZE - 1) {
+        prefix.write_element(Fr::from_be_bytes_mod_order(chunk));
+    }
+
+    (0..count)
+        .map(|i| {
+            let mut h = prefix.clone();
+            h.write_element(Fr::from(i as u64));
+            h.sum()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use ark_bn254::{Fq, Fr, G1Affine as G1};
+    use ark_ff::{BigInteger, Field, MontFp, PrimeField};
+    use sha3::{Digest, Keccak256};
+    use crate::hash2g1::{Expander, Hash2FieldBN254, HashToG1With};
+    use super::{hash_to_field_mimc, Error, MiMC, MIMC_CONSTANTS};
+
+    // The digests and points below must match gnark-crypto, as printed by this program
+    // (go mod init vectors && go get github.com/consensys/gnark-crypto@v0.14.0 && go run .):
+    //
+    //   package main
+    //
+    //   import (
+    //       "bytes"
+    //       "fmt"
+    //
+    //       "github.com/consensys/gnark-crypto/ecc/bn254"
+    //       "github.com/consensys/gnark-crypto/ecc/bn254/fp"
+    //       "github.com/consensys/gnark-crypto/ecc/bn254/fr"
+    //       "github.com/consensys/gnark-crypto/ecc/bn254/fr/mimc"
+    //   )
+    //
+    //   func sum(blocks ...[]byte) fr.Element {
+    //       h := mimc.NewMiMC()
+    //       for _, b := range blocks {
+    //           if _, err := h.Write(b); err != nil {
+    //               panic(err)
+    //           }
+    //       }
+    //       var e fr.Element
+    //       e.SetBytes(h.Sum(nil))
+    //       return e
+    //   }
+    //
+    //   func be32(n int) []byte {
+    //       var e fr.Element
+    //       b := e.SetUint64(uint64(n)).Bytes()
+    //       return b[:]
+    //   }
+    //
+    //   func chunks(s []byte) (c [][]byte) {
+    //       for ; len(s) > 31; s = s[31:] {
+    //           c = append(c, s[:31])
+    //       }
+    //       if len(s) > 0 {
+    //           c = append(c, s)
+    //       }
+    //       return c
+    //   }
+    //
+    //   // u_i with a fresh hasher, see hash_to_field_mimc
+    //   func hashToField(msg, dst []byte, i int) fp.Element {
+    //       blocks := append([][]byte{be32(len(dst))}, chunks(dst)...)
+    //       blocks = append(append(append(blocks, be32(len(msg))), chunks(msg)...), be32(i))
+    //       u := sum(blocks...)
+    //       var e fp.Element
+    //       b := u.Bytes()
+    //       e.SetBytes(b[:])
+    //       return e
+    //   }
+    //
+    //   func main() {
+    //       fmt.Println(sum(be32(1)).String())
+    //       fmt.Println(sum(bytes.Join([][]byte{be32(1), be32(2), be32(3)}, nil)).String())
+    //       fmt.Println(sum([]byte("abc")).String())
+    //
+    //       dst := []byte("QUUX-V01-CS02-with-BN254G1_MIMC_SVDW_RO_")
+    //       for _, msg := range []string{"", "abc", "abcdef0123456789"} {
+    //           u0, u1 := hashToField([]byte(msg), dst, 0), hashToField([]byte(msg), dst, 1)
+    //           q0, q1 := bn254.MapToG1(u0), bn254.MapToG1(u1)
+    //           var p bn254.G1Jac
+    //           var q bn254.G1Affine
+    //           q.FromJacobian(p.FromAffine(&q0).AddMixed(&q1))
+    //           fmt.Printf("%q u0=%s u1=%s x=%s y=%s\n", msg, u0.String(), u1.String(), q.X.String(), q.Y.String())
+    //       }
+    //   }
+
+    #[test]
+    fn mimc_constants_test() {
+        // initConstants in gnark-crypto: keccak256 chain seeded with "seed"
+        let mut rnd = Keccak256::digest(b"seed");
+        for c in MIMC_CONSTANTS.iter() {
+            rnd = Keccak256::digest(rnd);
+            assert_eq!(*c, Fr::from_be_bytes_mod_order(&rnd));
+        }
+    }
+
+    #[test]
+    fn mimc_vectors_test() {
+        let block = |x: u8| {
+            let mut b = [0u8; 32];
+            b[31] = x;
+            b
+        };
+
+        let mut h = MiMC::new();
+        h.write(&block(1)).unwrap();
+        assert_eq!(h.sum(), MontFp!("18045289051299654077710208499747278752099041449041972372412271818361923969579"));
+
+        let mut h = MiMC::new();
+        h.write(&[block(1), block(2), block(3)].concat()).unwrap();
+        assert_eq!(h.sum(), MontFp!("1594628425784724590720470781298820968689131300912933114724835803309940639844"));
+
+        let mut h = MiMC::new();
+        h.write(b"abc").unwrap();
+        assert_eq!(h.sum(), MontFp!("1403930096814559799523393211688985316287557201108728730193009934840984656738"));
+    }
+
+    #[test]
+    fn mimc_write_test() {
+        // short writes are left-padded to a full block
+        let mut a = MiMC::new();
+        a.write(b"abc").unwrap();
+        let mut b = MiMC::new();
+        let mut block = [0u8; 32];
+        block[29..].copy_from_slice(b"abc");
+        b.write(&block).unwrap();
+        assert_eq!(a.sum(), b.sum());
+
+        // writing two blocks at once is the same as writing them one by one
+        let mut c = MiMC::new();
+        c.write(&[block, block].concat()).unwrap();
+        b.write(&block).unwrap();
+        assert_eq!(b.sum(), c.sum());
+        assert_ne!(a.sum(), c.sum());
+
+        let mut d = MiMC::new();
+        d.write_element(Fr::from(0x616263u64));
+        assert_eq!(a.sum(), d.sum());
+
+        d.reset();
+        assert_eq!(d.sum(), Fr::ZERO);
+    }
+
+    #[test]
+    fn mimc_write_error_test() {
+        let mut h = MiMC::new();
+        h.write(b"abc").unwrap();
+        let sum = h.sum();
+
+        assert_eq!(h.write(&[0xffu8; 32]), Err(Error::NonCanonical));
+        assert_eq!(h.write(&[0u8; 33]), Err(Error::InvalidLength));
+
+        // r itself is not canonical, the error is reported even after a valid first block
+        let mut blocks = [0u8; 64];
+        blocks[32..].copy_from_slice(&Fr::MODULUS.to_bytes_be());
+        assert_eq!(h.write(&blocks), Err(Error::NonCanonical));
+
+        // a failed write leaves the state untouched
+        assert_eq!(h.sum(), sum);
+    }
+
+    #[test]
+    fn hash2field_mimc_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_MIMC_SVDW_RO_";
+
+        let u = Fq::hash_to_field_with(b"abc", dst, 2, Expander::MiMC);
+        assert_eq!(
+            u,
+            [
+                MontFp!("20911773113978894589429851878699635152436124972568554178386735714410553459791"),
+                MontFp!("726333539194392162852518363656875177825640262963931768835705852336372946152"),
+            ]
+        );
+        assert_eq!(u[..1], Fq::hash_to_field_with(b"abc", dst, 1, Expander::MiMC)[..]);
+
+        // the same digests over Fr
+        let v = Fr::hash_to_field_with(b"abc", dst, 2, Expander::MiMC);
+        assert_eq!(v, hash_to_field_mimc(b"abc", dst, 2));
+        assert_eq!(u[1].into_bigint(), v[1].into_bigint());
+
+        // XmdSha256 is the default hash_to_field
+        assert_eq!(Fq::hash_to_field_with(b"abc", dst, 2, Expander::default()), Fq::hash_to_field(b"abc", dst, 2));
+
+        // domain separation on both msg and dst
+        assert_ne!(u, Fq::hash_to_field_with(b"abd", dst, 2, Expander::MiMC));
+        assert_ne!(u, Fq::hash_to_field_with(b"abc", b"QUUX-V01-CS02-with-BN254G1_MIMC_SVDW_NU_", 2, Expander::MiMC));
+        assert_ne!(hash_to_field_mimc(b"", b"", 1), hash_to_field_mimc(&[0u8], b"", 1));
+
+        // u_i is the digest of the framed input, as a gnark circuit would compute it with Write
+        let mut h = MiMC::new();
+        h.write_element(Fr::from(dst.len() as u64));
+        h.write(&dst[..31]).unwrap();
+        h.write(&dst[31..]).unwrap();
+        h.write_element(Fr::from(3u64));
+        h.write(b"abc").unwrap();
+        h.write_element(Fr::from(1u64));
+        assert_eq!(v[1], h.sum());
+    }
+
+    #[test]
+    fn hash2field_mimc_restart_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_MIMC_SVDW_RO_";
+        let u = hash_to_field_mimc(b"abc", dst, 2);
+
+        let mut h = MiMC::new();
+        h.write_element(Fr::from(dst.len() as u64));
+        h.write(&dst[..31]).unwrap();
+        h.write(&dst[31..]).unwrap();
+        h.write_element(Fr::from(3u64));
+        h.write(b"abc").unwrap();
+        let prefix = h.clone();
+        h.write_element(Fr::from(0u64));
+        assert_eq!(u[0], h.sum());
+
+        // sum() keeps the state like gnark's Sum(nil), continuing after u_0 does not give u_1
+        h.write_element(Fr::from(1u64));
+        assert_ne!(u[1], h.sum());
+
+        // u_1 restarts from the prefix
+        let mut h = prefix;
+        h.write_element(Fr::from(1u64));
+        assert_eq!(u[1], h.sum());
+    }
+
+    #[test]
+    #[allow(non_snake_case)]
+    fn HashToG1With_mimc_test() {
+        let dst = b"QUUX-V01-CS02-with-BN254G1_MIMC_SVDW_RO_";
+        let vectors: [(&[u8], Fq, Fq); 3] = [
+            (
+                b"",
+                MontFp!("18057337822007548477508400214285349012531547962997681815174832927787162657094"),
+                MontFp!("17852047857628234663535329411867383004095428454273737780148140566095871936130"),
+            ),
+            (
+                b"abc",
+                MontFp!("14389520226725022388450283475997285322969476993987153846065326576790686986402"),
+                MontFp!("7213557200533882603465705657471125154499791957000294241288909055665803253207"),
+            ),
+            (
+                b"abcdef0123456789",
+                MontFp!("11251266433848388770477944516554153338475207700104838382368851417150019572243"),
+                MontFp!("3505094651785631802000137306661444509250669483036140989142388740148457587290"),
+            ),
+        ];
+        for (msg, x, y) in vectors {
+            assert_eq!(HashToG1With(msg, dst, Expander::MiMC), G1::new(x, y));
+        }
+    }
+}
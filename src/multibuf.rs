@@ -0,0 +1,466 @@
+use alloc::{vec, vec::Vec};
+use sha2::digest::generic_array::GenericArray;
+
+// Multi-buffer SHA-256: several independent messages are compressed side by side, one message per
+// SIMD lane (8 lanes with AVX2, 4 with SSE2). Other targets, and CPUs with the SHA extensions,
+// use sha2's own compression function one message at a time.
+// expand_message_batch uses it to run expand_message_xmd over a batch of messages at once.
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-hash-to-curve-16.html#name-expand_message_xmd
+
+const IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Backend {
+    Scalar,
+    #[cfg(target_arch = "x86_64")]
+    Sse2,
+    #[cfg(target_arch = "x86_64")]
+    Avx2,
+}
+
+impl Backend {
+    // the widest backend the running CPU supports. With the SHA extensions sha2 compresses a single
+    // block faster in hardware than the lanes do in software, so the scalar path is kept there.
+    pub(crate) fn detect() -> Backend {
+        #[cfg(all(target_arch = "x86_64", feature = "std"))]
+        {
+            if std::is_x86_feature_detected!("sha") {
+                return Backend::Scalar;
+            }
+            if std::is_x86_feature_detected!("avx2") {
+                return Backend::Avx2;
+            }
+            Backend::Sse2
+        }
+        #[cfg(all(target_arch = "x86_64", not(feature = "std")))]
+        {
+            if cfg!(target_feature = "sha") {
+                return Backend::Scalar;
+            }
+            if cfg!(target_feature = "avx2") {
+                return Backend::Avx2;
+            }
+            Backend::Sse2
+        }
+        #[cfg(not(target_arch = "x86_64"))]
+        {
+            Backend::Scalar
+        }
+    }
+}
+
+// One SIMD register holding word i of LANES independent SHA-256 computations
+trait Lanes: Copy {
+    const LANES: usize;
+    unsafe fn splat(x: u32) -> Self;
+    unsafe fn load(words: &[u32]) -> Self;
+    unsafe fn store(self, words: &mut [u32]);
+    unsafe fn add(self, other: Self) -> Self;
+    unsafe fn xor(self, other: Self) -> Self;
+    unsafe fn and(self, other: Self) -> Self;
+    // !self & other
+    unsafe fn andnot(self, other: Self) -> Self;
+    unsafe fn shr<const S: i32>(self) -> Self;
+    // rotate right by R, L = 32 - R
+    unsafe fn rotr<const R: i32, const L: i32>(self) -> Self;
+}
+
+#[inline(always)]
+unsafe fn compress<V: Lanes>(state: &mut [V; 8], w: &mut [V; 16]) {
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (i, k) in K.iter().enumerate() {
+        if i >= 16 {
+            // w[i] = σ1(w[i-2]) + w[i-7] + σ0(w[i-15]) + w[i-16]
+            let w15 = w[(i + 1) % 16];
+            let w2 = w[(i + 14) % 16];
+            let s0 = w15.rotr::<7, 25>().xor(w15.rotr::<18, 14>()).xor(w15.shr::<3>());
+            let s1 = w2.rotr::<17, 15>().xor(w2.rotr::<19, 13>()).xor(w2.shr::<10>());
+            w[i % 16] = w[i % 16].add(s0).add(w[(i + 9) % 16]).add(s1);
+        }
+
+        let s1 = e.rotr::<6, 26>().xor(e.rotr::<11, 21>()).xor(e.rotr::<25, 7>());
+        let ch = e.and(f).xor(e.andnot(g));
+        let t1 = h.add(s1).add(ch).add(V::splat(*k)).add(w[i % 16]);
+        let s0 = a.rotr::<2, 30>().xor(a.rotr::<13, 19>()).xor(a.rotr::<22, 10>());
+        let maj = a.and(b).xor(a.and(c)).xor(b.and(c));
+        let t2 = s0.add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.add(t2);
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.add(v);
+    }
+}
+
+// Runs compress over `blocks` 64 byte blocks of every message in the group, messages are the lanes
+#[inline(always)]
+unsafe fn compress_lanes<V: Lanes>(states: &mut [[u32; 8]], msgs: &[&[u8]], blocks: usize) {
+    let mut words = [0u32; 8];
+
+    let mut state = [V::splat(0); 8];
+    for (j, s) in state.iter_mut().enumerate() {
+        for (lane, st) in states.iter().enumerate() {
+            words[lane] = st[j];
+        }
+        *s = V::load(&words);
+    }
+
+    for block in 0..blocks {
+        let mut w = [V::splat(0); 16];
+        for (j, wj) in w.iter_mut().enumerate() {
+            for (lane, msg) in msgs.iter().enumerate() {
+                let off = 64 * block + 4 * j;
+                words[lane] = u32::from_be_bytes(msg[off..off + 4].try_into().unwrap());
+            }
+            *wj = V::load(&words);
+        }
+        compress(&mut state, &mut w);
+    }
+
+    for (j, s) in state.iter().enumerate() {
+        s.store(&mut words);
+        for (lane, st) in states.iter_mut().enumerate() {
+            st[j] = words[lane];
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+mod x86 {
+    use super::Lanes;
+    use core::arch::x86_64::*;
+
+    #[derive(Clone, Copy)]
+    pub(super) struct Sse2(__m128i);
+
+    impl Lanes for Sse2 {
+        const LANES: usize = 4;
+
+        #[inline(always)]
+        unsafe fn splat(x: u32) -> Self {
+            Sse2(_mm_set1_epi32(x as i32))
+        }
+        #[inline(always)]
+        unsafe fn load(words: &[u32]) -> Self {
+            Sse2(_mm_loadu_si128(words.as_ptr() as *const __m128i))
+        }
+        #[inline(always)]
+        unsafe fn store(self, words: &mut [u32]) {
+            _mm_storeu_si128(words.as_mut_ptr() as *mut __m128i, self.0)
+        }
+        #[inline(always)]
+        unsafe fn add(self, other: Self) -> Self {
+            Sse2(_mm_add_epi32(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn xor(self, other: Self) -> Self {
+            Sse2(_mm_xor_si128(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn and(self, other: Self) -> Self {
+            Sse2(_mm_and_si128(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn andnot(self, other: Self) -> Self {
+            Sse2(_mm_andnot_si128(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn shr<const S: i32>(self) -> Self {
+            Sse2(_mm_srli_epi32::<S>(self.0))
+        }
+        #[inline(always)]
+        unsafe fn rotr<const R: i32, const L: i32>(self) -> Self {
+            Sse2(_mm_or_si128(_mm_srli_epi32::<R>(self.0), _mm_slli_epi32::<L>(self.0)))
+        }
+    }
+
+    #[derive(Clone, Copy)]
+    pub(super) struct Avx2(__m256i);
+
+    impl Lanes for Avx2 {
+        const LANES: usize = 8;
+
+        #[inline(always)]
+        unsafe fn splat(x: u32) -> Self {
+            Avx2(_mm256_set1_epi32(x as i32))
+        }
+        #[inline(always)]
+        unsafe fn load(words: &[u32]) -> Self {
+            Avx2(_mm256_loadu_si256(words.as_ptr() as *const __m256i))
+        }
+        #[inline(always)]
+        unsafe fn store(self, words: &mut [u32]) {
+            _mm256_storeu_si256(words.as_mut_ptr() as *mut __m256i, self.0)
+        }
+        #[inline(always)]
+        unsafe fn add(self, other: Self) -> Self {
+            Avx2(_mm256_add_epi32(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn xor(self, other: Self) -> Self {
+            Avx2(_mm256_xor_si256(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn and(self, other: Self) -> Self {
+            Avx2(_mm256_and_si256(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn andnot(self, other: Self) -> Self {
+            Avx2(_mm256_andnot_si256(self.0, other.0))
+        }
+        #[inline(always)]
+        unsafe fn shr<const S: i32>(self) -> Self {
+            Avx2(_mm256_srli_epi32::<S>(self.0))
+        }
+        #[inline(always)]
+        unsafe fn rotr<const R: i32, const L: i32>(self) -> Self {
+            Avx2(_mm256_or_si256(_mm256_srli_epi32::<R>(self.0), _mm256_slli_epi32::<L>(self.0)))
+        }
+    }
+
+    #[target_feature(enable = "sse2")]
+    pub(super) unsafe fn compress_sse2(states: &mut [[u32; 8]], msgs: &[&[u8]], blocks: usize) {
+        super::compress_lanes::<Sse2>(states, msgs, blocks)
+    }
+
+    #[target_feature(enable = "avx2")]
+    pub(super) unsafe fn compress_avx2(states: &mut [[u32; 8]], msgs: &[&[u8]], blocks: usize) {
+        super::compress_lanes::<Avx2>(states, msgs, blocks)
+    }
+}
+
+fn compress_scalar(state: &mut [u32; 8], msg: &[u8]) {
+    for block in msg.chunks_exact(64) {
+        sha2::compress256(state, &[*GenericArray::from_slice(block)]);
+    }
+}
+
+// Compresses every padded message into its state. Lanes of a SIMD group run in lockstep over the
+// blocks they have in common, whatever is left of the longer messages is finished one by one.
+pub(crate) fn compress_many(backend: Backend, states: &mut [[u32; 8]], msgs: &[&[u8]]) {
+    let lanes = match backend {
+        Backend::Scalar => 1,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Sse2 => <x86::Sse2 as Lanes>::LANES,
+        #[cfg(target_arch = "x86_64")]
+        Backend::Avx2 => <x86::Avx2 as Lanes>::LANES,
+    };
+
+    for (states, msgs) in states.chunks_mut(lanes).zip(msgs.chunks(lanes)) {
+        let mut common = 0;
+        if msgs.len() == lanes && lanes > 1 {
+            common = msgs.iter().map(|m| m.len() / 64).min().unwrap();
+            match backend {
+                Backend::Scalar => {}
+                // sse2 is part of the x86_64 baseline, avx2 is only selected after detection
+                #[cfg(target_arch = "x86_64")]
+                Backend::Sse2 => unsafe { x86::compress_sse2(states, msgs, common) },
+                #[cfg(target_arch = "x86_64")]
+                Backend::Avx2 => unsafe { x86::compress_avx2(states, msgs, common) },
+            }
+        }
+        for (state, msg) in states.iter_mut().zip(msgs) {
+            compress_scalar(state, &msg[64 * common..]);
+        }
+    }
+}
+
+// Appends data || 0x80 || 0* || bit length, `prefix_len` bytes are assumed to be already compressed
+fn pad_into(buf: &mut Vec<u8>, data: &[&[u8]], prefix_len: usize) {
+    let start = buf.len();
+    let mut len = prefix_len;
+    for d in data {
+        buf.extend_from_slice(d);
+        len += d.len();
+    }
+    buf.push(0x80);
+    let padded = (buf.len() - start + 8).div_ceil(64) * 64;
+    buf.resize(start + padded - 8, 0);
+    buf.extend_from_slice(&((len as u64) * 8).to_be_bytes());
+}
+
+fn to_bytes(state: &[u32; 8]) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    for (o, s) in out.chunks_exact_mut(4).zip(state) {
+        o.copy_from_slice(&s.to_be_bytes());
+    }
+    out
+}
+
+// Compresses each padded message in `buf` (split at `offsets`) from `init`, returning the digests
+fn hash_padded(backend: Backend, init: [u32; 8], buf: &[u8], offsets: &[usize]) -> Vec<[u8; 32]> {
+    let msgs: Vec<&[u8]> = offsets.windows(2).map(|w| &buf[w[0]..w[1]]).collect();
+    let mut states = vec![init; msgs.len()];
+    compress_many(backend, &mut states, &msgs);
+    states.iter().map(to_bytes).collect()
+}
+
+// expand_message_batch runs expand_message_xmd with SHA-256 on every message, writing
+// len_in_bytes bytes per message into consecutive chunks of out. The output is byte-identical to
+// calling expand_message_into on each message.
+pub fn expand_message_batch(msgs: &[&[u8]], dst: &[u8], len_in_bytes: usize, out: &mut [u8]) {
+    expand_message_batch_with(Backend::detect(), msgs, dst, len_in_bytes, out)
+}
+
+pub(crate) fn expand_message_batch_with(backend: Backend, msgs: &[&[u8]], dst: &[u8], len_in_bytes: usize, out: &mut [u8]) {
+
+    let b_in_bytes: usize = 32;
+    let ell = len_in_bytes.div_ceil(b_in_bytes);
+
+    if ell > 255 {
+        panic!("ell was too big in expand_message_xmd");
+    }
+
+    if dst.len() > 255 {
+        panic!("dst size is invalid");
+    }
+
+    if out.len() != msgs.len() * len_in_bytes {
+        panic!("output size is invalid");
+    }
+
+    if msgs.is_empty() || len_in_bytes == 0 {
+        return;
+    }
+
+    let dst_prime = [dst, &[dst.len() as u8]];
+    let l_i_b_str = [(len_in_bytes >> 8) as u8, len_in_bytes as u8, 0u8];
+
+    // b_0 = H(Z_pad || msg || l_i_b_str || I2OSP(0, 1) || DST_prime), Z_pad is one full block of
+    // zeros shared by all messages so it is compressed once
+    let mut z_pad_state = IV;
+    compress_scalar(&mut z_pad_state, &[0u8; 64]);
+
+    let mut buf = Vec::new();
+    let mut offsets = vec![0];
+    for msg in msgs {
+        pad_into(&mut buf, &[msg, &l_i_b_str, dst_prime[0], dst_prime[1]], 64);
+        offsets.push(buf.len());
+    }
+    let b_0 = hash_padded(backend, z_pad_state, &buf, &offsets);
+
+    // b_i = H(strxor(b_0, b_(i - 1)) || I2OSP(i, 1) || DST_prime), with b_1 = H(b_0 || 1 || DST_prime)
+    let mut b_prev = b_0.clone();
+    for i in 1..=ell {
+        buf.clear();
+        offsets.truncate(1);
+        for (b0, prev) in b_0.iter().zip(&b_prev) {
+            let mut tmp = *b0;
+            if i > 1 {
+                for (t, p) in tmp.iter_mut().zip(prev) {
+                    *t ^= p;
+                }
+            }
+            pad_into(&mut buf, &[&tmp, &[i as u8], dst_prime[0], dst_prime[1]], 0);
+            offsets.push(buf.len());
+        }
+        b_prev = hash_padded(backend, IV, &buf, &offsets);
+
+        let start = (i - 1) * b_in_bytes;
+        let end = len_in_bytes.min(i * b_in_bytes);
+        for (o, b) in out.chunks_exact_mut(len_in_bytes).zip(&b_prev) {
+            o[start..end].copy_from_slice(&b[..end - start]);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{expand_message_batch, expand_message_batch_with, Backend};
+    use crate::hash2g1::expand_message_into;
+    use ark_std::rand::{Rng, RngCore};
+
+    fn backends() -> Vec<Backend> {
+        let mut backends = vec![Backend::Scalar];
+        #[cfg(target_arch = "x86_64")]
+        {
+            backends.push(Backend::Sse2);
+            if std::is_x86_feature_detected!("avx2") {
+                backends.push(Backend::Avx2);
+            }
+        }
+        backends
+    }
+
+    fn check(backend: Backend, msgs: &[Vec<u8>], dst: &[u8], len_in_bytes: usize) {
+        let msgs: Vec<&[u8]> = msgs.iter().map(|m| m.as_slice()).collect();
+        let mut out = vec![0u8; msgs.len() * len_in_bytes];
+        expand_message_batch_with(backend, &msgs, dst, len_in_bytes, &mut out);
+
+        for (msg, o) in msgs.iter().zip(out.chunks_exact(len_in_bytes.max(1))) {
+            let mut expected = vec![0u8; len_in_bytes];
+            expand_message_into(msg, dst, &mut expected);
+            assert_eq!(o, &expected[..], "{:?} {} {}", backend, msg.len(), len_in_bytes);
+        }
+    }
+
+    #[test]
+    fn expand_message_batch_diff_test() {
+        let mut rng = ark_std::test_rng();
+        let dst = b"QUUX-V01-CS02-with-BN254G1_XMD:SHA-256_SVDW_RO_";
+
+        for backend in backends() {
+            // batch sizes around the lane widths, message lengths around the block boundaries
+            for n in [1, 3, 4, 5, 8, 9, 17] {
+                for len in [0, 1, 55, 56, 63, 64, 119, 120, 200] {
+                    let msgs: Vec<Vec<u8>> = (0..n)
+                        .map(|_| {
+                            let mut m = vec![0u8; len];
+                            rng.fill_bytes(&mut m);
+                            m
+                        })
+                        .collect();
+                    for len_in_bytes in [32, 96, 128, 192] {
+                        check(backend, &msgs, dst, len_in_bytes);
+                    }
+                }
+            }
+
+            // messages of different lengths in the same group
+            let msgs: Vec<Vec<u8>> = (0..23)
+                .map(|_| {
+                    let mut m = vec![0u8; rng.gen_range(0..300)];
+                    rng.fill_bytes(&mut m);
+                    m
+                })
+                .collect();
+            check(backend, &msgs, dst, 128);
+
+            // dst lengths, output lengths that are not a multiple of 32 and the maximum one
+            check(backend, &msgs, b"", 33);
+            check(backend, &msgs, &[0x42u8; 255], 1);
+            check(backend, &msgs[..3], dst, 255 * 32);
+        }
+    }
+
+    #[test]
+    fn expand_message_batch_empty_test() {
+        let mut out = [];
+        expand_message_batch(&[], b"dst", 64, &mut out);
+        let mut out = [];
+        expand_message_batch(&[b"abc"], b"dst", 0, &mut out);
+    }
+}
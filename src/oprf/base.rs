@@ -0,0 +1,84 @@
+use ark_bn254::{Fr, G1Affine};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_ff::Zero;
+use ark_std::rand::Rng;
+use crate::glv::mul_glv_g1_ct;
+use super::{context_string, finalize_hash, serialize_element, unblind, MODE_OPRF};
+
+// OPRF mode, the client learns F(sk, input) and the server learns nothing
+// https://www.rfc-editor.org/rfc/rfc9497.html#name-oprf-protocol
+
+pub fn derive_key_pair(seed: &[u8; 32], info: &[u8]) -> Option<(Fr, G1Affine)> {
+    super::derive_key_pair(MODE_OPRF, seed, info)
+}
+
+// blind returns (blind, blindedElement), None if the input hashes to the identity
+pub fn blind<R: Rng + ?Sized>(input: &[u8], rng: &mut R) -> Option<(Fr, G1Affine)> {
+    super::blind(input, &context_string(MODE_OPRF), rng)
+}
+
+pub fn blind_evaluate(sk: &Fr, blinded: &G1Affine) -> G1Affine {
+    mul_glv_g1_ct(&blinded.into_group(), sk).into_affine()
+}
+
+pub fn finalize(input: &[u8], blind: &Fr, evaluated: &G1Affine) -> [u8; 32] {
+    let unblinded = unblind(blind, evaluated);
+    finalize_hash(&[input, &serialize_element(&unblinded)])
+}
+
+// evaluate computes the PRF output directly from the key, None if the input hashes to the identity
+pub fn evaluate(sk: &Fr, input: &[u8]) -> Option<[u8; 32]> {
+    evaluate_with_mode(MODE_OPRF, sk, input)
+}
+
+pub(super) fn evaluate_with_mode(mode: u8, sk: &Fr, input: &[u8]) -> Option<[u8; 32]> {
+    let input_element = super::hash_to_group(input, &context_string(mode));
+    if input_element.is_zero() {
+        return None;
+    }
+    let evaluated = mul_glv_g1_ct(&input_element, sk).into_affine();
+    Some(finalize_hash(&[input, &serialize_element(&evaluated)]))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::hex_to_bytes;
+    use super::{blind, blind_evaluate, derive_key_pair, evaluate, finalize};
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::{AffineRepr, CurveGroup};
+    use ark_ff::UniformRand;
+    use std::str::FromStr;
+
+    #[test]
+    fn oprf_test() {
+        let mut rng = ark_std::test_rng();
+        let (sk, pk) = derive_key_pair(&[0xa3; 32], b"test key").unwrap();
+        assert_eq!(pk, (G1Affine::generator() * sk).into_affine());
+
+        for input in [&b""[..], b"input", &[0x5a; 100]] {
+            let (r, blinded) = blind(input, &mut rng).unwrap();
+            let evaluated = blind_evaluate(&sk, &blinded);
+            let output = finalize(input, &r, &evaluated);
+            assert_eq!(Some(output), evaluate(&sk, input));
+
+            // a fresh blind gives the same output
+            let (r2, blinded2) = blind(input, &mut rng).unwrap();
+            assert_ne!(blinded, blinded2);
+            assert_eq!(finalize(input, &r2, &blind_evaluate(&sk, &blinded2)), output);
+
+            // another key or another input gives another output
+            assert_ne!(finalize(input, &r, &blind_evaluate(&Fr::rand(&mut rng), &blinded)), output);
+            assert_ne!(finalize(b"other", &r, &evaluated), output);
+        }
+    }
+
+    // generated by this implementation
+    #[test]
+    fn vectors_test() {
+        let (sk, _) = derive_key_pair(&[0xa3; 32], b"test key").unwrap();
+        assert_eq!(sk, Fr::from_str("16551984300570993562226709785033930891137567634400399679820133612296729756152").unwrap());
+        assert_eq!(evaluate(&sk, b"").unwrap().to_vec(), hex_to_bytes("a0eaaf84c5b9976eed3e4009362a3cd9d510559115ed9465738da754e0b80a5c"));
+        assert_eq!(evaluate(&sk, b"input").unwrap().to_vec(), hex_to_bytes("945c901d04c832d516354df4a11808385c0fc3ef4e02c7d9431b089bb5f42e8e"));
+    }
+}
@@ -0,0 +1,164 @@
+use ark_bn254::{Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::{Field, UniformRand, Zero};
+use ark_serialize::CanonicalSerialize;
+use ark_std::rand::Rng;
+use alloc::vec::Vec;
+use sha2::{Digest, Sha256};
+use crate::glv::{mul_glv_g1, mul_glv_g1_ct};
+use crate::hash2g1::{Hash2FieldBN254, HashToG1_projective};
+
+// Oblivious pseudorandom functions following RFC 9497 with a BN254 G1 ciphersuite of our own:
+// HashToGroup is HashToG1 and HashToScalar is hash_to_field over Fr, both with the DST prefixes of
+// the RFC, Hash is SHA-256. Elements and scalars use the compressed arkworks encoding (32 bytes each).
+// base is the OPRF mode, voprf adds a DLEQ proof that the server used the key committed to in its
+// public key and poprf additionally binds a public input info to the evaluation.
+// https://www.rfc-editor.org/rfc/rfc9497.html
+pub mod base;
+pub mod poprf;
+pub mod voprf;
+
+pub const IDENTIFIER: &[u8] = b"BN254-SHA256";
+
+const MODE_OPRF: u8 = 0x00;
+const MODE_VOPRF: u8 = 0x01;
+const MODE_POPRF: u8 = 0x02;
+
+// the DLEQ proof (c, s)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Proof {
+    pub c: Fr,
+    pub s: Fr,
+}
+
+// contextString = "OPRFV1-" || I2OSP(mode, 1) || "-" || identifier
+fn context_string(mode: u8) -> Vec<u8> {
+    [b"OPRFV1-", &[mode][..], b"-", IDENTIFIER].concat()
+}
+
+fn hash_to_group(input: &[u8], ctx: &[u8]) -> G1Projective {
+    HashToG1_projective(input, &[b"HashToGroup-", ctx].concat())
+}
+
+fn hash_to_scalar(input: &[u8], ctx: &[u8]) -> Fr {
+    Fr::hash_to_field(input, &[b"HashToScalar-", ctx].concat(), 1)[0]
+}
+
+fn serialize_element(p: &G1Affine) -> [u8; 32] {
+    let mut out = [0u8; 32];
+    p.serialize_compressed(&mut out[..]).unwrap();
+    out
+}
+
+fn random_scalar<R: Rng + ?Sized>(rng: &mut R) -> Fr {
+    loop {
+        let r = Fr::rand(rng);
+        if !r.is_zero() {
+            return r;
+        }
+    }
+}
+
+// I2OSP(len(x), 2) || x for every x, followed by the label
+fn length_prefixed(parts: &[&[u8]], label: &[u8]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for p in parts {
+        out.extend_from_slice(&(p.len() as u16).to_be_bytes());
+        out.extend_from_slice(p);
+    }
+    out.extend_from_slice(label);
+    out
+}
+
+// DeriveKeyPair, None in the negligible case that 256 counters all give a zero key
+fn derive_key_pair(mode: u8, seed: &[u8; 32], info: &[u8]) -> Option<(Fr, G1Affine)> {
+    let ctx = context_string(mode);
+    let dst = [b"DeriveKeyPair", &ctx[..]].concat();
+    // seed || I2OSP(len(info), 2) || info || I2OSP(counter, 1)
+    let mut derive_input = [&seed[..], &(info.len() as u16).to_be_bytes(), info].concat();
+
+    for counter in 0..=255u8 {
+        derive_input.push(counter);
+        let sk = Fr::hash_to_field(&derive_input, &dst, 1)[0];
+        derive_input.pop();
+        if !sk.is_zero() {
+            return Some((sk, mul_glv_g1_ct(&G1Projective::generator(), &sk).into_affine()));
+        }
+    }
+    None
+}
+
+// Blind, None if the input hashes to the identity
+fn blind<R: Rng + ?Sized>(input: &[u8], ctx: &[u8], rng: &mut R) -> Option<(Fr, G1Affine)> {
+    let input_element = hash_to_group(input, ctx);
+    if input_element.is_zero() {
+        return None;
+    }
+    let blind = random_scalar(rng);
+    Some((blind, mul_glv_g1_ct(&input_element, &blind).into_affine()))
+}
+
+fn unblind(blind: &Fr, evaluated: &G1Affine) -> G1Affine {
+    mul_glv_g1_ct(&evaluated.into_group(), &blind.inverse().unwrap()).into_affine()
+}
+
+// Hash(I2OSP(len(x), 2) || x ... || "Finalize")
+fn finalize_hash(parts: &[&[u8]]) -> [u8; 32] {
+    Sha256::digest(length_prefixed(parts, b"Finalize")).into()
+}
+
+// ComputeComposites, with k given the server computes Z = [k] M directly (ComputeCompositesFast)
+fn compute_composites(k: Option<&Fr>, b: &G1Affine, c: &[G1Affine], d: &[G1Affine], ctx: &[u8]) -> (G1Projective, G1Projective) {
+    let seed_dst = [b"Seed-", ctx].concat();
+    let seed: [u8; 32] = Sha256::digest(length_prefixed(&[&serialize_element(b), &seed_dst], b"")).into();
+
+    let mut m = G1Projective::zero();
+    let mut z = G1Projective::zero();
+    for (i, (ci, di)) in c.iter().zip(d).enumerate() {
+        // I2OSP(len(seed), 2) || seed || I2OSP(i, 2) || I2OSP(len(Ci), 2) || Ci || I2OSP(len(Di), 2) || Di || "Composite"
+        let transcript = [
+            &length_prefixed(&[&seed], b"")[..],
+            &(i as u16).to_be_bytes(),
+            &length_prefixed(&[&serialize_element(ci), &serialize_element(di)], b"Composite"),
+        ].concat();
+        let di_scalar = hash_to_scalar(&transcript, ctx);
+        m += mul_glv_g1(&ci.into_group(), &di_scalar);
+        if k.is_none() {
+            z += mul_glv_g1(&di.into_group(), &di_scalar);
+        }
+    }
+    if let Some(k) = k {
+        z = mul_glv_g1_ct(&m, k);
+    }
+    (m, z)
+}
+
+fn challenge(b: &G1Affine, m: &G1Projective, z: &G1Projective, t2: &G1Projective, t3: &G1Projective, ctx: &[u8]) -> Fr {
+    let points = G1Projective::normalize_batch(&[*m, *z, *t2, *t3]);
+    let strings = points.iter().map(serialize_element).collect::<Vec<_>>();
+    let transcript = length_prefixed(
+        &[&serialize_element(b), &strings[0], &strings[1], &strings[2], &strings[3]],
+        b"Challenge",
+    );
+    hash_to_scalar(&transcript, ctx)
+}
+
+// GenerateProof that log_A(B) = log_C(D) = k for every pair (C[i], D[i])
+fn generate_proof<R: Rng + ?Sized>(k: &Fr, a: &G1Affine, b: &G1Affine, c: &[G1Affine], d: &[G1Affine], ctx: &[u8], rng: &mut R) -> Proof {
+    let (m, z) = compute_composites(Some(k), b, c, d, ctx);
+    let r = random_scalar(rng);
+    let t2 = mul_glv_g1_ct(&a.into_group(), &r);
+    let t3 = mul_glv_g1_ct(&m, &r);
+    let c = challenge(b, &m, &z, &t2, &t3, ctx);
+    Proof { c, s: r - c * k }
+}
+
+fn verify_proof(a: &G1Affine, b: &G1Affine, c: &[G1Affine], d: &[G1Affine], proof: &Proof, ctx: &[u8]) -> bool {
+    if c.is_empty() || c.len() != d.len() {
+        return false;
+    }
+    let (m, z) = compute_composites(None, b, c, d, ctx);
+    let t2 = mul_glv_g1(&a.into_group(), &proof.s) + mul_glv_g1(&b.into_group(), &proof.c);
+    let t3 = mul_glv_g1(&m, &proof.s) + mul_glv_g1(&z, &proof.c);
+    challenge(b, &m, &z, &t2, &t3, ctx) == proof.c
+}
@@ -0,0 +1,96 @@
+use ark_bn254::{Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_ff::{Field, Zero};
+use ark_std::rand::Rng;
+use crate::glv::{mul_glv_g1, mul_glv_g1_ct};
+use super::{context_string, finalize_hash, generate_proof, hash_to_group, hash_to_scalar, serialize_element, unblind, verify_proof, Proof, MODE_POPRF};
+
+// POPRF mode, a verifiable evaluation under the key tweaked by a public input info: t = sk + m with
+// m = HashToScalar("Info" || I2OSP(len(info), 2) || info) and F(sk, input, info) = [1 / t] H(input)
+// https://www.rfc-editor.org/rfc/rfc9497.html#name-poprf-protocol
+
+fn info_scalar(info: &[u8]) -> Fr {
+    let framed_info = [b"Info", &(info.len() as u16).to_be_bytes()[..], info].concat();
+    hash_to_scalar(&framed_info, &context_string(MODE_POPRF))
+}
+
+pub fn derive_key_pair(seed: &[u8; 32], info: &[u8]) -> Option<(Fr, G1Affine)> {
+    super::derive_key_pair(MODE_POPRF, seed, info)
+}
+
+// blind returns (blind, blindedElement, tweakedKey), None if the input hashes to the identity or the
+// tweaked key is the identity
+pub fn blind<R: Rng + ?Sized>(input: &[u8], info: &[u8], pk: &G1Affine, rng: &mut R) -> Option<(Fr, G1Affine, G1Affine)> {
+    let m = info_scalar(info);
+    let tweaked_key = mul_glv_g1(&G1Projective::generator(), &m) + pk;
+    if tweaked_key.is_zero() {
+        return None;
+    }
+    let (blind, blinded) = super::blind(input, &context_string(MODE_POPRF), rng)?;
+    Some((blind, blinded, tweaked_key.into_affine()))
+}
+
+// blind_evaluate returns the evaluation and its proof, None if sk + m = 0
+pub fn blind_evaluate<R: Rng + ?Sized>(sk: &Fr, blinded: &G1Affine, info: &[u8], rng: &mut R) -> Option<(G1Affine, Proof)> {
+    let t = *sk + info_scalar(info);
+    if t.is_zero() {
+        return None;
+    }
+    let evaluated = mul_glv_g1_ct(&blinded.into_group(), &t.inverse().unwrap()).into_affine();
+    let tweaked_key = mul_glv_g1_ct(&G1Projective::generator(), &t).into_affine();
+    // the proof is for log_G(tweakedKey) = log_evaluated(blinded)
+    let proof = generate_proof(&t, &G1Affine::generator(), &tweaked_key, &[evaluated], &[*blinded], &context_string(MODE_POPRF), rng);
+    Some((evaluated, proof))
+}
+
+pub fn finalize(input: &[u8], blind: &Fr, evaluated: &G1Affine, blinded: &G1Affine, proof: &Proof, info: &[u8], tweaked_key: &G1Affine) -> Option<[u8; 32]> {
+    if !verify_proof(&G1Affine::generator(), tweaked_key, &[*evaluated], &[*blinded], proof, &context_string(MODE_POPRF)) {
+        return None;
+    }
+    let unblinded = unblind(blind, evaluated);
+    Some(finalize_hash(&[input, info, &serialize_element(&unblinded)]))
+}
+
+pub fn evaluate(sk: &Fr, input: &[u8], info: &[u8]) -> Option<[u8; 32]> {
+    let input_element = hash_to_group(input, &context_string(MODE_POPRF));
+    if input_element.is_zero() {
+        return None;
+    }
+    let t = *sk + info_scalar(info);
+    if t.is_zero() {
+        return None;
+    }
+    let evaluated = mul_glv_g1_ct(&input_element, &t.inverse().unwrap()).into_affine();
+    Some(finalize_hash(&[input, info, &serialize_element(&evaluated)]))
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{blind, blind_evaluate, derive_key_pair, evaluate, finalize, info_scalar};
+
+    #[test]
+    fn poprf_test() {
+        let mut rng = ark_std::test_rng();
+        let (sk, pk) = derive_key_pair(&[0xa3; 32], b"test key").unwrap();
+
+        for (input, info) in [(&b""[..], &b""[..]), (b"input", b"info"), (&[0x5a; 100], b"info")] {
+            let (r, blinded, tweaked_key) = blind(input, info, &pk, &mut rng).unwrap();
+            let (evaluated, proof) = blind_evaluate(&sk, &blinded, info, &mut rng).unwrap();
+            let output = finalize(input, &r, &evaluated, &blinded, &proof, info, &tweaked_key).unwrap();
+            assert_eq!(Some(output), evaluate(&sk, input, info));
+
+            // info is bound to the output and to the proof
+            assert_ne!(Some(output), evaluate(&sk, input, b"other info"));
+            let (evaluated2, proof2) = blind_evaluate(&sk, &blinded, b"other info", &mut rng).unwrap();
+            assert!(finalize(input, &r, &evaluated2, &blinded, &proof2, info, &tweaked_key).is_none());
+            assert!(finalize(input, &r, &evaluated, &blinded, &proof, info, &pk).is_none());
+        }
+
+        // sk + m = 0 cannot be evaluated
+        let bad_sk = -info_scalar(b"info");
+        let (_, blinded, _) = blind(b"input", b"info", &pk, &mut rng).unwrap();
+        assert!(blind_evaluate(&bad_sk, &blinded, b"info", &mut rng).is_none());
+        assert!(evaluate(&bad_sk, b"input", b"info").is_none());
+    }
+}
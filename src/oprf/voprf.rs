@@ -0,0 +1,111 @@
+use ark_bn254::{Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup};
+use ark_std::rand::Rng;
+use alloc::vec::Vec;
+use crate::glv::mul_glv_g1_ct;
+use super::{context_string, finalize_hash, generate_proof, serialize_element, unblind, verify_proof, Proof, MODE_VOPRF};
+
+// VOPRF mode, the server proves that it evaluated with the key behind its public key
+// https://www.rfc-editor.org/rfc/rfc9497.html#name-voprf-protocol
+
+pub fn derive_key_pair(seed: &[u8; 32], info: &[u8]) -> Option<(Fr, G1Affine)> {
+    super::derive_key_pair(MODE_VOPRF, seed, info)
+}
+
+// blind returns (blind, blindedElement), None if the input hashes to the identity
+pub fn blind<R: Rng + ?Sized>(input: &[u8], rng: &mut R) -> Option<(Fr, G1Affine)> {
+    super::blind(input, &context_string(MODE_VOPRF), rng)
+}
+
+pub fn blind_evaluate<R: Rng + ?Sized>(sk: &Fr, pk: &G1Affine, blinded: &G1Affine, rng: &mut R) -> (G1Affine, Proof) {
+    let (evaluated, proof) = blind_evaluate_batch(sk, pk, &[*blinded], rng);
+    (evaluated[0], proof)
+}
+
+// blind_evaluate_batch evaluates several blinded elements under a single proof
+pub fn blind_evaluate_batch<R: Rng + ?Sized>(sk: &Fr, pk: &G1Affine, blinded: &[G1Affine], rng: &mut R) -> (Vec<G1Affine>, Proof) {
+    let evaluated: Vec<G1Projective> = blinded.iter().map(|b| mul_glv_g1_ct(&b.into_group(), sk)).collect();
+    let evaluated = G1Projective::normalize_batch(&evaluated);
+    let proof = generate_proof(sk, &G1Affine::generator(), pk, blinded, &evaluated, &context_string(MODE_VOPRF), rng);
+    (evaluated, proof)
+}
+
+// finalize verifies the proof and returns the PRF output, None if the proof does not verify
+pub fn finalize(input: &[u8], blind: &Fr, evaluated: &G1Affine, blinded: &G1Affine, pk: &G1Affine, proof: &Proof) -> Option<[u8; 32]> {
+    finalize_batch(&[input], &[*blind], &[*evaluated], &[*blinded], pk, proof).map(|out| out[0])
+}
+
+pub fn finalize_batch(inputs: &[&[u8]], blinds: &[Fr], evaluated: &[G1Affine], blinded: &[G1Affine], pk: &G1Affine, proof: &Proof) -> Option<Vec<[u8; 32]>> {
+    if inputs.len() != blinds.len() || inputs.len() != evaluated.len() {
+        return None;
+    }
+    if !verify_proof(&G1Affine::generator(), pk, blinded, evaluated, proof, &context_string(MODE_VOPRF)) {
+        return None;
+    }
+    Some(inputs.iter().zip(blinds).zip(evaluated).map(|((input, blind), evaluated)| {
+        finalize_hash(&[input, &serialize_element(&unblind(blind, evaluated))])
+    }).collect())
+}
+
+pub fn evaluate(sk: &Fr, input: &[u8]) -> Option<[u8; 32]> {
+    super::base::evaluate_with_mode(MODE_VOPRF, sk, input)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{blind, blind_evaluate, blind_evaluate_batch, derive_key_pair, evaluate, finalize, finalize_batch};
+    use crate::oprf::{base, Proof};
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::CurveGroup;
+    use ark_ff::One;
+
+    #[test]
+    fn voprf_test() {
+        let mut rng = ark_std::test_rng();
+        let (sk, pk) = derive_key_pair(&[0xa3; 32], b"test key").unwrap();
+
+        for input in [&b""[..], b"input", &[0x5a; 100]] {
+            let (r, blinded) = blind(input, &mut rng).unwrap();
+            let (evaluated, proof) = blind_evaluate(&sk, &pk, &blinded, &mut rng);
+            let output = finalize(input, &r, &evaluated, &blinded, &pk, &proof).unwrap();
+            assert_eq!(Some(output), evaluate(&sk, input));
+
+            // the modes are domain separated
+            assert_ne!(Some(output), base::evaluate(&sk, input));
+
+            // a server evaluating with another key, or lying about its output, is caught
+            let (other_sk, other_pk) = derive_key_pair(&[0xa4; 32], b"test key").unwrap();
+            let (bad, bad_proof) = blind_evaluate(&other_sk, &other_pk, &blinded, &mut rng);
+            assert!(finalize(input, &r, &bad, &blinded, &pk, &bad_proof).is_none());
+            assert!(finalize(input, &r, &bad, &blinded, &pk, &proof).is_none());
+            assert!(finalize(input, &r, &evaluated, &blinded, &other_pk, &proof).is_none());
+            let tampered = Proof { c: proof.c, s: proof.s + Fr::one() };
+            assert!(finalize(input, &r, &evaluated, &blinded, &pk, &tampered).is_none());
+        }
+    }
+
+    #[test]
+    fn voprf_batch_test() {
+        let mut rng = ark_std::test_rng();
+        let (sk, pk) = derive_key_pair(&[0xa3; 32], b"").unwrap();
+        let inputs: Vec<&[u8]> = vec![b"a", b"b", b"c", b"d"];
+
+        let (blinds, blinded): (Vec<Fr>, Vec<G1Affine>) = inputs.iter().map(|i| blind(i, &mut rng).unwrap()).unzip();
+        let (evaluated, proof) = blind_evaluate_batch(&sk, &pk, &blinded, &mut rng);
+        let outputs = finalize_batch(&inputs, &blinds, &evaluated, &blinded, &pk, &proof).unwrap();
+        for (input, output) in inputs.iter().zip(outputs) {
+            assert_eq!(Some(output), evaluate(&sk, input));
+        }
+
+        // swapping two evaluations breaks the proof
+        let mut swapped = evaluated.clone();
+        swapped.swap(0, 1);
+        assert!(finalize_batch(&inputs, &blinds, &swapped, &blinded, &pk, &proof).is_none());
+        assert!(finalize_batch(&inputs[1..], &blinds[1..], &evaluated[1..], &blinded[1..], &pk, &proof).is_none());
+
+        let mut wrong = evaluated.clone();
+        wrong[3] = (wrong[3] + wrong[2]).into_affine();
+        assert!(finalize_batch(&inputs, &blinds, &wrong, &blinded, &pk, &proof).is_none());
+    }
+}
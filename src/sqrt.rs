@@ -0,0 +1,135 @@
+use ark_bn254::{fq::Fq, fq2::Fq2};
+use ark_ff::{Field, MontFp, Zero};
+
+// SqrtRatio provides the is_square and sqrt_ratio helpers of the hash to curve draft, so that a map
+// gets a square root candidate out of the same exponentiation that decides whether it is a square.
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-hash-to-curve-16.html#name-sqrt_ratio-subroutines
+pub trait SqrtRatio: Field {
+    // the fixed non-square Z used by sqrt_ratio
+    const Z: Self;
+
+    // is_square(x) is true iff x is zero or a square
+    fn is_square(&self) -> bool;
+
+    // sqrt_ratio(u, v) returns (true, sqrt(u / v)) if u / v is square and (false, sqrt(Z * u / v))
+    // otherwise. v must be non-zero.
+    fn sqrt_ratio(u: &Self, v: &Self) -> (bool, Self);
+}
+
+// (p - 3) / 4
+const C1_3MOD4: [u64; 4] = [0x4f082305b61f3f51, 0x65e05aa45a1c72a3, 0x6e14116da0605617, 0x0c19139cb84c680a];
+
+// p = 3 mod 4, so Z = -1 and c2 = sqrt(-Z) = 1
+// https://www.ietf.org/archive/id/draft-irtf-cfrg-hash-to-curve-16.html#name-optimized-sqrt_ratio-for-q-
+impl SqrtRatio for Fq {
+    const Z: Fq = MontFp!("-1");
+
+    fn is_square(&self) -> bool {
+        Fq::sqrt_ratio(self, &Fq::ONE).0
+    }
+
+    fn sqrt_ratio(u: &Fq, v: &Fq) -> (bool, Fq) {
+        let mut tv1 = v.square();       //    1. tv1 = v^2
+        let tv2 = *u * v;               //    2. tv2 = u * v
+        tv1 *= tv2;                     //    3. tv1 = tv1 * tv2
+        let mut y1 = tv1.pow(C1_3MOD4); //    4. y1 = tv1^c1
+        y1 *= tv2;                      //    5. y1 = y1 * tv2
+                                        //    6. y2 = y1 * c2, c2 = 1
+        let tv3 = y1.square() * v;      //    7. tv3 = y1^2, 8. tv3 = tv3 * v
+        let is_qr = tv3 == *u;          //    9. isQR = tv3 == u
+        (is_qr, y1)                     //   10. y = CMOV(y2, y1, isQR)
+    }
+}
+
+// 1 / 2
+const TWO_INV: Fq = MontFp!("10944121435919637611123202872628637544348155578648911831344518947322613104292");
+
+// sqrt(-82), 82 = N(9 + i)
+const SQRT_MINUS_82: Fq = MontFp!("9402326650280006191761379138962807908170813540258190189176893397805789916985");
+
+// Fq2 = Fq[i] / (i² + 1). a = a0 + a1 i is a square iff its norm a0² + a1² is a square in Fq, and
+// given alpha = sqrt(a0² + a1²) exactly one of (a0 ± alpha) / 2 is a square x0², which gives the
+// root x0 + a1 / (2 x0) i. Each sqrt_ratio costs two exponentiations in Fq and one inversion.
+impl SqrtRatio for Fq2 {
+    // 9 + i is neither a square nor a cube in Fq2
+    const Z: Fq2 = Fq2::new(MontFp!("9"), MontFp!("1"));
+
+    fn is_square(&self) -> bool {
+        self.norm().is_square()
+    }
+
+    fn sqrt_ratio(u: &Fq2, v: &Fq2) -> (bool, Fq2) {
+        let mut a = if *v == Fq2::ONE { *u } else { *u * v.inverse().expect("v must be non-zero") };
+
+        // sqrt_ratio over Fq returns sqrt(-N(a)) when N(a) is not a square, then
+        // N(Z * a) = 82 * N(a) and sqrt(82 * N(a)) = sqrt(-82) * sqrt(-N(a))
+        let (is_qr, mut alpha) = Fq::sqrt_ratio(&a.norm(), &Fq::ONE);
+        if !is_qr {
+            a *= Self::Z;
+            alpha *= SQRT_MINUS_82;
+        }
+
+        if a.c1.is_zero() {
+            // a0 or -a0 is a square, (x1 i)² = -x1²
+            let (a0_qr, x) = Fq::sqrt_ratio(&a.c0, &Fq::ONE);
+            return if a0_qr { (is_qr, Fq2::new(x, Fq::ZERO)) } else { (is_qr, Fq2::new(Fq::ZERO, x)) };
+        }
+
+        // delta = (a0 + alpha) / 2. If delta is not a square then x = sqrt(-delta) is the imaginary
+        // part of the root whose real part squares to (a0 - alpha) / 2
+        let delta = (a.c0 + alpha) * TWO_INV;
+        let (delta_qr, x) = Fq::sqrt_ratio(&delta, &Fq::ONE);
+        let other = a.c1 * (x.double()).inverse().unwrap();
+        let root = if delta_qr { Fq2::new(x, other) } else { Fq2::new(other, x) };
+
+        (is_qr, root)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{SqrtRatio, C1_3MOD4, SQRT_MINUS_82, TWO_INV};
+    use ark_bn254::{fq::Fq, fq2::Fq2};
+    use ark_ff::{BigInteger, BigInteger256, Field, PrimeField, UniformRand};
+
+    #[test]
+    fn constants_test() {
+        let mut c1 = BigInteger256::new(C1_3MOD4);
+        c1.muln(2);
+        c1.add_with_carry(&BigInteger256::from(3u64));
+        assert_eq!(c1, Fq::MODULUS);
+
+        assert_eq!(TWO_INV.double(), Fq::ONE);
+        assert_eq!(SQRT_MINUS_82.square(), -Fq::from(82u64));
+        assert!(!<Fq as SqrtRatio>::Z.legendre().is_qr());
+        assert!(!<Fq2 as SqrtRatio>::Z.legendre().is_qr());
+    }
+
+    fn check<F: SqrtRatio>(u: F, v: F) {
+        let (is_qr, y) = F::sqrt_ratio(&u, &v);
+        assert_eq!(is_qr, (u / v).legendre().is_qr() || u.is_zero());
+        if is_qr {
+            assert_eq!(y.square() * v, u);
+        } else {
+            assert_eq!(y.square() * v, F::Z * u);
+        }
+        assert_eq!((u / v).is_square(), is_qr);
+    }
+
+    #[test]
+    fn sqrt_ratio_test() {
+        let mut rng = ark_std::test_rng();
+        for _ in 0..1000 {
+            check(Fq::rand(&mut rng), Fq::rand(&mut rng));
+            check(Fq::rand(&mut rng), Fq::ONE);
+            check(Fq2::rand(&mut rng), Fq2::rand(&mut rng));
+            check(Fq2::rand(&mut rng), Fq2::ONE);
+            // the a1 = 0 branch, with a0 square and non-square
+            check(Fq2::new(Fq::rand(&mut rng), Fq::ZERO), Fq2::ONE);
+        }
+        check(Fq::ZERO, Fq::ONE);
+        check(Fq2::ZERO, Fq2::ONE);
+        check(-Fq2::ONE, Fq2::ONE);
+    }
+}
@@ -0,0 +1,19 @@
+// helpers shared by the test modules
+
+use ark_bn254::{g2, Fq2, G2Affine, G2Projective};
+use ark_ec::{short_weierstrass::SWCurveConfig, AffineRepr, Group};
+use ark_ff::{Field, UniformRand};
+
+pub(crate) fn hex_to_bytes(s: &str) -> Vec<u8> {
+    (0..s.len()).step_by(2).map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap()).collect()
+}
+
+// random points of E'(Fq2), almost never in the r-torsion. The Jacobian coordinates are not normalized.
+pub(crate) fn random_twist_point(rng: &mut impl ark_std::rand::Rng) -> G2Projective {
+    loop {
+        let x = Fq2::rand(rng);
+        if let Some(y) = (x.square() * x + g2::Config::COEFF_B).sqrt() {
+            return G2Affine::new_unchecked(x, y).into_group().double() + G2Projective::generator();
+        }
+    }
+}
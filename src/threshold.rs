@@ -0,0 +1,152 @@
+use ark_bn254::{Fr, G1Affine, G1Projective, G2Affine};
+use ark_ec::{CurveGroup, VariableBaseMSM};
+use ark_ff::{batch_inversion, Field, UniformRand, Zero};
+use ark_std::rand::Rng;
+use alloc::vec::Vec;
+use crate::bls::min_sig;
+
+// t-of-n threshold BLS with a trusted dealer. The secret key is shared with a random polynomial
+// f of degree t - 1 over Fr, f(0) = sk, and party i holds (i, f(i)) for i = 1..n. Partial signatures
+// are [f(i)] HashToG1(msg, dst) and any t of them combine into [sk] HashToG1(msg, dst) by Lagrange
+// interpolation at 0 in the exponent. Share public keys live in G2 as for bls::min_sig.
+// https://www.iacr.org/archive/pkc2003/25670031/25670031.pdf
+
+// split_secret returns the shares (i, f(i)), i = 1..n. It panics unless 1 <= t <= n.
+pub fn split_secret<R: Rng + ?Sized>(sk: &Fr, t: usize, n: usize, rng: &mut R) -> Vec<(u64, Fr)> {
+    if t == 0 || t > n {
+        panic!("threshold must be between 1 and the number of parties");
+    }
+
+    let mut coeffs = alloc::vec![*sk];
+    coeffs.extend((1..t).map(|_| Fr::rand(rng)));
+
+    (1..=n as u64).map(|i| {
+        // Horner
+        let x = Fr::from(i);
+        let y = coeffs.iter().rev().fold(Fr::zero(), |acc, c| acc * x + c);
+        (i, y)
+    }).collect()
+}
+
+pub fn share_public_key(share: &Fr) -> G2Affine {
+    min_sig::sk_to_pk(share)
+}
+
+pub fn partial_sign(share: &Fr, msg: &[u8], dst: &[u8]) -> G1Affine {
+    min_sig::core_sign(share, msg, dst)
+}
+
+// partial_verify checks a partial signature against the public key of the share that produced it
+pub fn partial_verify(share_pk: &G2Affine, msg: &[u8], dst: &[u8], partial: &G1Affine) -> bool {
+    min_sig::core_verify(share_pk, msg, partial, dst)
+}
+
+// verify checks a combined signature against the group public key [sk] g2
+pub fn verify(pk: &G2Affine, msg: &[u8], dst: &[u8], sig: &G1Affine) -> bool {
+    min_sig::core_verify(pk, msg, sig, dst)
+}
+
+// lagrange_coefficients returns l_i(0) = prod_{j != i} x_j / (x_j - x_i) for the given indices, or
+// None if an index is zero or repeated
+pub fn lagrange_coefficients(indices: &[u64]) -> Option<Vec<Fr>> {
+    let xs: Vec<Fr> = indices.iter().map(|i| Fr::from(*i)).collect();
+    if xs.iter().any(|x| x.is_zero()) {
+        return None;
+    }
+
+    let mut num = Vec::with_capacity(xs.len());
+    let mut den = Vec::with_capacity(xs.len());
+    for (i, xi) in xs.iter().enumerate() {
+        let mut n = Fr::ONE;
+        let mut d = Fr::ONE;
+        for (j, xj) in xs.iter().enumerate() {
+            if i != j {
+                n *= xj;
+                d *= *xj - xi;
+            }
+        }
+        if d.is_zero() {
+            return None;
+        }
+        num.push(n);
+        den.push(d);
+    }
+
+    batch_inversion(&mut den);
+    Some(num.iter().zip(den).map(|(n, d)| *n * d).collect())
+}
+
+// combine interpolates t partial signatures (i, sig_i) into the group signature. With fewer than t
+// partials the result is a valid point that does not verify. None if partials is empty or an index
+// is zero or repeated.
+pub fn combine(partials: &[(u64, G1Affine)]) -> Option<G1Affine> {
+    if partials.is_empty() {
+        return None;
+    }
+    let indices: Vec<u64> = partials.iter().map(|(i, _)| *i).collect();
+    let coeffs = lagrange_coefficients(&indices)?;
+    let bases: Vec<G1Affine> = partials.iter().map(|(_, s)| *s).collect();
+    Some(G1Projective::msm(&bases, &coeffs).unwrap().into_affine())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::{combine, lagrange_coefficients, partial_sign, partial_verify, share_public_key, split_secret, verify};
+    use crate::bls::min_sig;
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ff::{UniformRand, Zero};
+
+    const DST: &[u8] = b"THRESHOLD_BLS_BN254G1_XMD:SHA-256_SVDW_RO_";
+
+    #[test]
+    fn lagrange_test() {
+        let mut rng = ark_std::test_rng();
+        let sk = Fr::rand(&mut rng);
+        let shares = split_secret(&sk, 3, 5, &mut rng);
+        let coeffs = lagrange_coefficients(&[1, 3, 4]).unwrap();
+        let res = coeffs.iter().zip([0, 2, 3]).fold(Fr::zero(), |acc, (l, i)| acc + *l * shares[i].1);
+        assert_eq!(res, sk);
+
+        assert!(lagrange_coefficients(&[1, 2, 1]).is_none());
+        assert!(lagrange_coefficients(&[0, 2]).is_none());
+    }
+
+    #[test]
+    fn threshold_sign_test() {
+        let mut rng = ark_std::test_rng();
+        let (t, n) = (3, 5);
+        let sk = Fr::rand(&mut rng);
+        let pk = min_sig::sk_to_pk(&sk);
+        let shares = split_secret(&sk, t, n, &mut rng);
+        let expected = min_sig::core_sign(&sk, b"msg", DST);
+
+        let partials: Vec<(u64, G1Affine)> = shares.iter().map(|(i, s)| (*i, partial_sign(s, b"msg", DST))).collect();
+        for ((_, s), (_, p)) in shares.iter().zip(&partials) {
+            assert!(partial_verify(&share_public_key(s), b"msg", DST, p));
+            assert!(!partial_verify(&share_public_key(s), b"other msg", DST, p));
+            assert!(!partial_verify(&pk, b"msg", DST, p));
+        }
+
+        // every subset of at least t parties gives the same signature, smaller ones do not verify
+        for mask in 1u32..(1 << n) {
+            let subset: Vec<(u64, G1Affine)> = (0..n).filter(|i| mask & (1 << i) != 0).map(|i| partials[i]).collect();
+            let sig = combine(&subset).unwrap();
+            if subset.len() >= t {
+                assert_eq!(sig, expected);
+                assert!(verify(&pk, b"msg", DST, &sig));
+            } else {
+                assert!(!verify(&pk, b"msg", DST, &sig));
+            }
+        }
+
+        assert!(combine(&[]).is_none());
+        assert!(combine(&[partials[0], partials[1], partials[0]]).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn split_secret_threshold_test() {
+        split_secret(&Fr::from(1u64), 4, 3, &mut ark_std::test_rng());
+    }
+}
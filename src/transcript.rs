@@ -0,0 +1,220 @@
+use ark_bn254::{Fr, G1Affine, G2Affine};
+use ark_serialize::CanonicalSerialize;
+use sha2::{Digest, Sha256};
+use crate::hash2g1::{expand_message_into, Hash2FieldBN254, HashToG1};
+use crate::hash2g2::HashToG2;
+
+// A Fiat-Shamir transcript in the spirit of Merlin. Every operation is absorbed into a running SHA-256
+// as op || I2OSP(len(label), 4) || label || I2OSP(len(data), 4) || data, with a distinct op byte per
+// kind of input and of challenge, so that no two sequences of operations share an encoding. A
+// challenge absorbs its own label first and then feeds the digest of the transcript so far to
+// expand_message_xmd, hash_to_field over Fr or HashToG1/HashToG2, each under its own DST.
+// Points and scalars are absorbed in the compressed arkworks encoding.
+// https://merlin.cool/transcript/ops.html
+
+const PROTOCOL: &[u8] = b"BN254-TRANSCRIPT-V01";
+
+pub const CHALLENGE_BYTES_DST: &[u8] = b"BN254-TRANSCRIPT-V01-CHALLENGE-BYTES_XMD:SHA-256_";
+pub const CHALLENGE_SCALAR_DST: &[u8] = b"BN254-TRANSCRIPT-V01-CHALLENGE-SCALAR_XMD:SHA-256_";
+pub const CHALLENGE_G1_DST: &[u8] = b"BN254-TRANSCRIPT-V01-CHALLENGE-BN254G1_XMD:SHA-256_SVDW_RO_";
+pub const CHALLENGE_G2_DST: &[u8] = b"BN254-TRANSCRIPT-V01-CHALLENGE-BN254G2_XMD:SHA-256_SVDW_RO_";
+
+const OP_DOMAIN_SEPARATOR: u8 = 0x00;
+const OP_MESSAGE: u8 = 0x01;
+const OP_SCALAR: u8 = 0x02;
+const OP_POINT_G1: u8 = 0x03;
+const OP_POINT_G2: u8 = 0x04;
+const OP_CHALLENGE_BYTES: u8 = 0x10;
+const OP_CHALLENGE_SCALAR: u8 = 0x11;
+const OP_CHALLENGE_G1: u8 = 0x12;
+const OP_CHALLENGE_G2: u8 = 0x13;
+
+#[derive(Clone)]
+pub struct Transcript {
+    state: Sha256,
+}
+
+impl Transcript {
+    // new starts a transcript for the protocol named by label
+    pub fn new(label: &[u8]) -> Self {
+        let mut t = Transcript { state: Sha256::new() };
+        t.absorb(OP_DOMAIN_SEPARATOR, PROTOCOL, label);
+        t
+    }
+
+    fn absorb(&mut self, op: u8, label: &[u8], data: &[u8]) {
+        self.state.update([op]);
+        self.state.update((label.len() as u32).to_be_bytes());
+        self.state.update(label);
+        self.state.update((data.len() as u32).to_be_bytes());
+        self.state.update(data);
+    }
+
+    // the digest of everything absorbed so far, the challenge label included
+    fn challenge_seed(&mut self, op: u8, label: &[u8]) -> [u8; 32] {
+        self.absorb(op, label, &[]);
+        self.state.clone().finalize().into()
+    }
+
+    pub fn append_message(&mut self, label: &[u8], msg: &[u8]) {
+        self.absorb(OP_MESSAGE, label, msg);
+    }
+
+    pub fn append_scalar(&mut self, label: &[u8], k: &Fr) {
+        let mut bytes = [0u8; 32];
+        k.serialize_compressed(&mut bytes[..]).unwrap();
+        self.absorb(OP_SCALAR, label, &bytes);
+    }
+
+    pub fn append_point_g1(&mut self, label: &[u8], p: &G1Affine) {
+        let mut bytes = [0u8; 32];
+        p.serialize_compressed(&mut bytes[..]).unwrap();
+        self.absorb(OP_POINT_G1, label, &bytes);
+    }
+
+    pub fn append_point_g2(&mut self, label: &[u8], p: &G2Affine) {
+        let mut bytes = [0u8; 64];
+        p.serialize_compressed(&mut bytes[..]).unwrap();
+        self.absorb(OP_POINT_G2, label, &bytes);
+    }
+
+    // challenge_bytes fills out, at most 255 * 32 bytes as for expand_message_xmd
+    pub fn challenge_bytes(&mut self, label: &[u8], out: &mut [u8]) {
+        let seed = self.challenge_seed(OP_CHALLENGE_BYTES, label);
+        expand_message_into(&seed, CHALLENGE_BYTES_DST, out);
+    }
+
+    pub fn challenge_scalar(&mut self, label: &[u8]) -> Fr {
+        let seed = self.challenge_seed(OP_CHALLENGE_SCALAR, label);
+        Fr::hash_to_field(&seed, CHALLENGE_SCALAR_DST, 1)[0]
+    }
+
+    // challenge_point_g1 returns a point of G1 with unknown discrete log
+    pub fn challenge_point_g1(&mut self, label: &[u8]) -> G1Affine {
+        let seed = self.challenge_seed(OP_CHALLENGE_G1, label);
+        HashToG1(&seed, CHALLENGE_G1_DST)
+    }
+
+    // challenge_point_g2 returns a point of G2 with unknown discrete log. It depends on the SVDW
+    // flavour of the enabled feature, like HashToG2.
+    pub fn challenge_point_g2(&mut self, label: &[u8]) -> G2Affine {
+        let seed = self.challenge_seed(OP_CHALLENGE_G2, label);
+        HashToG2(&seed, CHALLENGE_G2_DST)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::Transcript;
+    use crate::hash2g1::is_in_g1_subgroup;
+    use crate::hash2g2::is_in_g2_subgroup;
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use std::str::FromStr;
+
+    fn transcript() -> Transcript {
+        let mut t = Transcript::new(b"test protocol");
+        t.append_message(b"statement", b"x = 5");
+        t.append_point_g1(b"commitment", &G1Affine::generator());
+        t
+    }
+
+    #[test]
+    fn determinism_test() {
+        let mut a = transcript();
+        let mut b = transcript();
+        assert_eq!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+        assert_eq!(a.challenge_point_g1(b"h"), b.challenge_point_g1(b"h"));
+
+        // every challenge moves the state forward
+        let mut t = transcript();
+        assert_ne!(t.challenge_scalar(b"c"), t.challenge_scalar(b"c"));
+
+        // clones fork the transcript
+        let mut t = transcript();
+        let mut fork = t.clone();
+        t.append_message(b"m", b"1");
+        fork.append_message(b"m", b"2");
+        assert_ne!(t.challenge_scalar(b"c"), fork.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn order_sensitivity_test() {
+        let g1 = G1Affine::generator();
+        let h1: G1Affine = (g1 + g1).into();
+
+        let mut a = Transcript::new(b"p");
+        a.append_point_g1(b"A", &g1);
+        a.append_point_g1(b"B", &h1);
+        let mut b = Transcript::new(b"p");
+        b.append_point_g1(b"B", &h1);
+        b.append_point_g1(b"A", &g1);
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        // same data, different labels
+        let mut a = Transcript::new(b"p");
+        a.append_point_g1(b"A", &g1);
+        let mut b = Transcript::new(b"p");
+        b.append_point_g1(b"B", &g1);
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        // the boundary between label and data, or between two messages, is not malleable
+        let mut a = Transcript::new(b"p");
+        a.append_message(b"ab", b"c");
+        let mut b = Transcript::new(b"p");
+        b.append_message(b"a", b"bc");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+        let mut a = Transcript::new(b"p");
+        a.append_message(b"m", b"ab");
+        a.append_message(b"m", b"c");
+        let mut b = Transcript::new(b"p");
+        b.append_message(b"m", b"a");
+        b.append_message(b"m", b"bc");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        // the kind of the input is bound too
+        let mut a = Transcript::new(b"p");
+        a.append_scalar(b"x", &Fr::from(1u64));
+        let mut b = Transcript::new(b"p");
+        let mut bytes = [0u8; 32];
+        bytes[0] = 1;
+        b.append_message(b"x", &bytes);
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+
+        // and so are the protocol, the challenge label and the challenge kind
+        assert_ne!(Transcript::new(b"p").challenge_scalar(b"c"), Transcript::new(b"q").challenge_scalar(b"c"));
+        assert_ne!(Transcript::new(b"p").challenge_scalar(b"c"), Transcript::new(b"p").challenge_scalar(b"d"));
+        let mut a = Transcript::new(b"p");
+        let _ = a.challenge_scalar(b"c");
+        let mut b = Transcript::new(b"p");
+        let _ = b.challenge_point_g1(b"c");
+        assert_ne!(a.challenge_scalar(b"c"), b.challenge_scalar(b"c"));
+    }
+
+    #[test]
+    fn challenge_points_test() {
+        let mut t = transcript();
+        let p = t.challenge_point_g1(b"h");
+        let q = t.challenge_point_g2(b"h");
+        assert!(is_in_g1_subgroup(&p) && !p.is_zero());
+        assert!(is_in_g2_subgroup(&q) && !q.is_zero());
+        assert_ne!(p, t.challenge_point_g1(b"h"));
+        assert_ne!(q, t.challenge_point_g2(b"h"));
+
+        let mut a = [0u8; 100];
+        let mut b = [0u8; 100];
+        transcript().challenge_bytes(b"nonce", &mut a);
+        transcript().challenge_bytes(b"nonce", &mut b);
+        assert_eq!(a, b);
+        assert_ne!(a, [0u8; 100]);
+    }
+
+    // generated by this implementation
+    #[test]
+    fn vectors_test() {
+        let mut t = transcript();
+        t.append_scalar(b"response", &Fr::from(42u64));
+        assert_eq!(t.challenge_scalar(b"challenge"), Fr::from_str("10775699731596042773580553747602429971360669247167532675608330045327049191234").unwrap());
+    }
+}
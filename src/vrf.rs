@@ -0,0 +1,204 @@
+use ark_bn254::{Fr, G1Affine, G1Projective};
+use ark_ec::{AffineRepr, CurveGroup, Group};
+use ark_serialize::{CanonicalDeserialize, CanonicalSerialize};
+use sha2::{Digest, Sha256};
+use crate::glv::{mul_glv_g1, mul_glv_g1_ct};
+use crate::hash2g1::{is_in_g1_subgroup, Hash2FieldBN254, HashToG1_projective};
+
+// ECVRF over BN254 G1, following RFC 9381 with the hash to curve variant of encode_to_curve:
+// H = HashToG1(PK || alpha), Gamma = [x] H, and a Schnorr-style proof (c, s) that log_B(Y) = log_H(Gamma).
+// The challenge and the nonce are derived with hash_to_field over Fr instead of a truncated SHA-256
+// and RFC 6979, so c is a full scalar. Points and scalars use the compressed arkworks encoding.
+// https://www.rfc-editor.org/rfc/rfc9381.html#name-elliptic-curve-vrf-ecvrf
+
+pub const SUITE_STRING: &[u8] = b"BN254G1-SHA256-SVDW";
+
+// "ECVRF_" || h2c_suite_ID_string || suite_string
+pub const HASH_TO_CURVE_DST: &[u8] = b"ECVRF_BN254G1_XMD:SHA-256_SVDW_RO_BN254G1-SHA256-SVDW";
+pub const HASH_TO_SCALAR_DST: &[u8] = b"ECVRF_HashToScalar_BN254G1-SHA256-SVDW";
+
+const POINT_LEN: usize = 32;
+const SCALAR_LEN: usize = 32;
+
+// Gamma || c || s
+pub const PROOF_LEN: usize = POINT_LEN + 2 * SCALAR_LEN;
+
+fn point_to_string(p: &G1Affine) -> [u8; POINT_LEN] {
+    let mut out = [0u8; POINT_LEN];
+    p.serialize_compressed(&mut out[..]).unwrap();
+    out
+}
+
+fn scalar_to_string(k: &Fr) -> [u8; SCALAR_LEN] {
+    let mut out = [0u8; SCALAR_LEN];
+    k.serialize_compressed(&mut out[..]).unwrap();
+    out
+}
+
+fn hash_to_scalar(data: &[&[u8]]) -> Fr {
+    Fr::hash_to_field(&data.concat(), HASH_TO_SCALAR_DST, 1)[0]
+}
+
+// ECVRF_encode_to_curve, with PK_string as the salt
+fn encode_to_curve(pk: &G1Affine, alpha: &[u8]) -> G1Projective {
+    HashToG1_projective(&[&point_to_string(pk)[..], alpha].concat(), HASH_TO_CURVE_DST)
+}
+
+// ECVRF_nonce_generation, deterministic in the secret key and H
+fn nonce_generation(sk: &Fr, h: &[u8; POINT_LEN]) -> Fr {
+    hash_to_scalar(&[SUITE_STRING, &[0x01], &scalar_to_string(sk), h])
+}
+
+// ECVRF_challenge_generation(Y, H, Gamma, U, V)
+fn challenge_generation(points: [&G1Affine; 5]) -> Fr {
+    let mut data: alloc::vec::Vec<&[u8]> = alloc::vec![SUITE_STRING, &[0x02]];
+    let strings = points.map(point_to_string);
+    data.extend(strings.iter().map(|s| &s[..]));
+    data.push(&[0x00]);
+    hash_to_scalar(&data)
+}
+
+// ECVRF_decode_proof, None if Gamma is not a point of G1 or c, s are not canonical scalars
+fn decode_proof(pi: &[u8; PROOF_LEN]) -> Option<(G1Affine, Fr, Fr)> {
+    let gamma = G1Affine::deserialize_compressed(&pi[..POINT_LEN]).ok()?;
+    let c = Fr::deserialize_compressed(&pi[POINT_LEN..POINT_LEN + SCALAR_LEN]).ok()?;
+    let s = Fr::deserialize_compressed(&pi[POINT_LEN + SCALAR_LEN..]).ok()?;
+    Some((gamma, c, s))
+}
+
+// ECVRF_validate_key rejects the identity and points off the curve
+fn validate_key(pk: &G1Affine) -> bool {
+    !pk.is_zero() && is_in_g1_subgroup(pk)
+}
+
+pub fn public_key(sk: &Fr) -> G1Affine {
+    mul_glv_g1_ct(&G1Projective::generator(), sk).into_affine()
+}
+
+// prove returns pi = Gamma || c || s for the input alpha
+pub fn prove(sk: &Fr, alpha: &[u8]) -> [u8; PROOF_LEN] {
+    let y = public_key(sk);
+    let h = encode_to_curve(&y, alpha);
+    let h_affine = h.into_affine();
+    let gamma = mul_glv_g1_ct(&h, sk).into_affine();
+    let k = nonce_generation(sk, &point_to_string(&h_affine));
+    let u = mul_glv_g1_ct(&G1Projective::generator(), &k).into_affine();
+    let v = mul_glv_g1_ct(&h, &k).into_affine();
+    let c = challenge_generation([&y, &h_affine, &gamma, &u, &v]);
+    let s = k + c * sk;
+
+    let mut pi = [0u8; PROOF_LEN];
+    pi[..POINT_LEN].copy_from_slice(&point_to_string(&gamma));
+    pi[POINT_LEN..POINT_LEN + SCALAR_LEN].copy_from_slice(&scalar_to_string(&c));
+    pi[POINT_LEN + SCALAR_LEN..].copy_from_slice(&scalar_to_string(&s));
+    pi
+}
+
+// proof_to_hash returns beta = SHA-256(suite_string || 0x03 || Gamma || 0x00), G1 has cofactor 1.
+// It does not verify the proof.
+pub fn proof_to_hash(pi: &[u8; PROOF_LEN]) -> Option<[u8; 32]> {
+    let (gamma, _, _) = decode_proof(pi)?;
+    Some(gamma_to_hash(&gamma))
+}
+
+fn gamma_to_hash(gamma: &G1Affine) -> [u8; 32] {
+    Sha256::new()
+        .chain_update(SUITE_STRING)
+        .chain_update([0x03])
+        .chain_update(point_to_string(gamma))
+        .chain_update([0x00])
+        .finalize()
+        .into()
+}
+
+// verify returns beta if pi is a valid proof for alpha under pk
+pub fn verify(pk: &G1Affine, pi: &[u8; PROOF_LEN], alpha: &[u8]) -> Option<[u8; 32]> {
+    if !validate_key(pk) {
+        return None;
+    }
+    let (gamma, c, s) = decode_proof(pi)?;
+    let h = encode_to_curve(pk, alpha);
+
+    // U = [s] B - [c] Y, V = [s] H - [c] Gamma
+    let u = (mul_glv_g1(&G1Projective::generator(), &s) - mul_glv_g1(&pk.into_group(), &c)).into_affine();
+    let v = (mul_glv_g1(&h, &s) - mul_glv_g1(&gamma.into_group(), &c)).into_affine();
+    let c_prime = challenge_generation([pk, &h.into_affine(), &gamma, &u, &v]);
+
+    if c == c_prime {
+        Some(gamma_to_hash(&gamma))
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use crate::test_utils::hex_to_bytes;
+    use super::{proof_to_hash, prove, public_key, verify, PROOF_LEN};
+    use ark_bn254::{Fr, G1Affine};
+    use ark_ec::AffineRepr;
+    use ark_ff::UniformRand;
+    use std::str::FromStr;
+
+    #[test]
+    fn prove_verify_test() {
+        let mut rng = ark_std::test_rng();
+        for _ in 0..5 {
+            let sk = Fr::rand(&mut rng);
+            let pk = public_key(&sk);
+            let pi = prove(&sk, b"sample");
+            let beta = verify(&pk, &pi, b"sample").unwrap();
+            assert_eq!(Some(beta), proof_to_hash(&pi));
+
+            // deterministic and unique per input
+            assert_eq!(prove(&sk, b"sample"), pi);
+            assert_ne!(proof_to_hash(&prove(&sk, b"test")).unwrap(), beta);
+
+            assert!(verify(&pk, &pi, b"test").is_none());
+            assert!(verify(&public_key(&Fr::rand(&mut rng)), &pi, b"sample").is_none());
+            assert!(verify(&G1Affine::zero(), &pi, b"sample").is_none());
+        }
+    }
+
+    #[test]
+    fn tampered_proof_test() {
+        let sk = Fr::from(42u64);
+        let pk = public_key(&sk);
+        let pi = prove(&sk, b"sample");
+        for i in 0..PROOF_LEN {
+            for bit in [0x01, 0x80] {
+                let mut bad = pi;
+                bad[i] ^= bit;
+                assert!(verify(&pk, &bad, b"sample").is_none());
+            }
+        }
+
+        // s + r is not a canonical scalar
+        let mut bad = pi;
+        bad[64..].copy_from_slice(&[0xff; 32]);
+        assert!(proof_to_hash(&bad).is_none());
+    }
+
+    // generated by this implementation
+    #[test]
+    fn vectors_test() {
+        let vectors = [
+            ("1", &b""[..],
+             "8559523e9da9d9a68e897d410300eb285e3d53881f223ec65e09ab4080ffa183b4baf297f6679e55606480feb07e795d754d8e6e3cf3f84e1d52dc30d88e0604ebd423fe6c598dc54c6a9292665f7fd7f255fd686a59d3d134181790ddf59514",
+             "10cc72ec9edb7260d6cf766d6630038f060fa07c3072afe51b9590a1987e5253"),
+            ("42", b"sample",
+             "ad8ff6130c1eabb34cee9eeaf5d960b8bfdb054b6b425f69be0f6ec53bcd8f281fd48ad40b91cf04e3a7ed353d82affc55c06b99730d0d394abfb1766f5951261220692b54b995f077f8773dd6676e963f1738f9fa13f56287529be8dc1c7e13",
+             "c485c48683360e6990743967f531f9299189cddac1e599c6fe14a3f6f63b1c17"),
+            ("12345678901234567890123456789012345678901234567890", b"test",
+             "a69bf13a69f3b9a00d7104157487563c935744485866f95fca4a06a524ba8fa6a2f363bb352f8a52f580e74702d1d20abb1a4daee1de4389c5b97bed51b05f06b609e22d6b07bef7fd0ef700f111ed9e6647edff30a0bf153882635e5a0c221f",
+             "c0338677c4ecfc430faa515423b81d9eaf42eff3b86edb9450923b758fca9ef7"),
+        ];
+        for (sk, alpha, pi, beta) in vectors {
+            let sk = Fr::from_str(sk).unwrap();
+            let proof = prove(&sk, alpha);
+            assert_eq!(proof.to_vec(), hex_to_bytes(pi));
+            assert_eq!(verify(&public_key(&sk), &proof, alpha).unwrap().to_vec(), hex_to_bytes(beta));
+        }
+    }
+}